@@ -0,0 +1,29 @@
+// Maps arbitrary symbols onto a compact integer alphabet, as expected by the suffix array construction.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// Replaces every symbol with its rank among the distinct symbols of xs.
+/// The order is preserved, so the result sorts its suffixes the same way as xs.
+/// Result is in 0..sigma range, where sigma <= n is the number of distinct symbols.
+/// O(n + sigma log sigma) time.
+pub fn compact<T: Ord + Hash>(xs: &[T]) -> Vec<usize> {
+  let mut symbols: Vec<&T> = xs.iter().collect::<HashSet<_>>().into_iter().collect();
+  symbols.sort_unstable();
+  let ranks: HashMap<&T, usize> = symbols.into_iter().enumerate().map(|(r, x)| (x, r)).collect();
+  xs.iter().map(|x| ranks[x]).collect()
+}
+
+/// Like `compact`, for bytes: counting over the 256 possible values instead of hashing.
+/// O(n) time.
+pub fn compact_bytes(xs: &[u8]) -> Vec<usize> {
+  let mut present = [false; 256];
+  xs.iter().for_each(|&x| present[x as usize] = true);
+  let mut ranks = [0; 256];
+  let mut rank = 0;
+  for x in 0..256 {
+    ranks[x] = rank;
+    if present[x] { rank += 1 }
+  }
+  xs.iter().map(|&x| ranks[x as usize]).collect()
+}
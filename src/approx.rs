@@ -0,0 +1,211 @@
+// Approximate pattern matching with lce queries between the text and the pattern,
+// which jump over the matching stretches in O(1) (Landau and Vishkin).
+
+use std::hash::Hash;
+
+use crate::generalized::GeneralizedSARMQ;
+
+/// Occurrence of a pattern in a text, up to the listed mismatches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+  /// Index in the text where the occurrence starts.
+  pub position: usize,
+  /// Offsets in the pattern of the mismatching symbols, increasing,
+  /// so `text[position + x] != pattern[x]` exactly for x listed.
+  pub mismatches: Vec<usize>,
+}
+
+/// Finds all positions where pattern occurs in text with at most k mismatches (Hamming distance).
+/// O((n + m) log sigma + nk) time: after building the lce structure over both,
+/// every position is checked with at most k + 1 lce queries.
+pub fn k_mismatch_occurrences<T: Ord + Hash>(text: &[T], pattern: &[T], k: usize) -> Vec<Occurrence> {
+  let (n, m) = (text.len(), pattern.len());
+  if m > n {
+    return vec![];
+  }
+  let lce = GeneralizedSARMQ::create_generalized_sarmq(&[text, pattern]);
+  (0..=n - m).filter_map(|position| {
+    let mut mismatches = vec![];
+    let mut offset = 0;
+    loop {
+      offset += lce.query((0, position + offset), (1, offset));
+      if offset >= m {
+        return Some(Occurrence { position, mismatches });
+      }
+      if mismatches.len() == k {
+        return None;
+      }
+      mismatches.push(offset);
+      offset += 1;
+    }
+  }).collect()
+}
+
+/// Occurrence of a pattern in a text, up to the given number of edits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditOccurrence {
+  /// Index in the text right after the occurrence.
+  pub end: usize,
+  /// Least edit distance of the pattern to a substring of the text ending at `end`.
+  pub distance: usize,
+  /// One alignment of that distance, filled in by `k_difference_alignments`.
+  pub alignment: Option<Alignment>,
+}
+
+/// Alignment of a pattern to `text[start..end]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alignment {
+  pub start: usize,
+  /// Extended CIGAR string, with the text as the reference: runs of `=` for matches, `X` for substitutions,
+  /// `I` for pattern symbols missing in the text and `D` for text symbols missing in the pattern, e.g. `3=1X2=1I`.
+  pub cigar: String,
+}
+
+// no row reached yet, low enough to stay below any row after adding 1
+const NONE: isize = isize::MIN / 2;
+
+/// Finds all end positions in text where pattern occurs with at most k edits (Levenshtein distance),
+/// along with the least distance there, in increasing order of the ends.
+/// Landau-Vishkin: for every error count and diagonal (text index - pattern index) keeps the furthest
+/// reached pattern index, extended over matching stretches with lce queries.
+/// O((n + m) log sigma + nk) time, O(n + m) space.
+pub fn k_difference_occurrences<T: Ord + Hash>(text: &[T], pattern: &[T], k: usize) -> Vec<EditOccurrence> {
+  let lce = GeneralizedSARMQ::create_generalized_sarmq(&[text, pattern]);
+  search(&lce, pattern.len(), k)
+}
+
+/// Like `k_difference_occurrences`, with an alignment for every occurrence.
+/// An alignment costs another O((m + k) k) time, rerunning the search around its occurrence.
+pub fn k_difference_alignments<T: Ord + Hash>(text: &[T], pattern: &[T], k: usize) -> Vec<EditOccurrence> {
+  let lce = GeneralizedSARMQ::create_generalized_sarmq(&[text, pattern]);
+  let mut occurrences = search(&lce, pattern.len(), k);
+  for o in occurrences.iter_mut() {
+    o.alignment = Some(align(&lce, o.end, o.distance));
+  }
+  occurrences
+}
+
+fn search(lce: &GeneralizedSARMQ, m: usize, k: usize) -> Vec<EditOccurrence> {
+  let diagonals = Diagonals::new(lce, 0, lce.doc_len(0), k);
+  let mut distances = vec![None; diagonals.len()];
+  let mut level = vec![];
+  for e in 0..=k {
+    level = diagonals.next_level(e, &level);
+    for (distance, &row) in distances.iter_mut().zip(&level) {
+      if distance.is_none() && row == m as isize {
+        *distance = Some(e);
+      }
+    }
+  }
+  distances.into_iter().enumerate().filter_map(|(idx, distance)| {
+    distance.map(|distance| EditOccurrence { end: (diagonals.diagonal(idx) + m as isize) as usize, distance, alignment: None })
+  }).collect()
+}
+
+/// Traces back an alignment of the given distance ending at end, over the part of the text it can span.
+fn align(lce: &GeneralizedSARMQ, end: usize, distance: usize) -> Alignment {
+  let m = lce.doc_len(1);
+  let from = end.saturating_sub(m + distance);
+  let diagonals = Diagonals::new(lce, from, end, distance);
+  let mut levels: Vec<Vec<isize>> = vec![];
+  for e in 0..=distance {
+    let level = diagonals.next_level(e, levels.last().map_or(&[], |l| l));
+    levels.push(level);
+  }
+  // (row, col) is reachable with at most e edits, iff row is at most the furthest one on its diagonal
+  let reach = |d: isize, e: usize, row: isize| {
+    diagonals.index(d).is_some_and(|idx| row <= levels[e][idx])
+  };
+  let (mut d, mut e, mut row) = ((end - from) as isize - m as isize, distance, m as isize);
+  let mut ops = vec![];
+  while row > 0 {
+    while e > 0 && reach(d, e - 1, row) {
+      e -= 1;
+    }
+    let col = row + d;
+    if col > 0 && diagonals.matches(row - 1, col - 1) {
+      ops.push('=');
+      row -= 1;
+    } else if col > 0 && e > 0 && reach(d, e - 1, row - 1) {
+      ops.push('X');
+      row -= 1;
+      e -= 1;
+    } else if e > 0 && reach(d + 1, e - 1, row - 1) {
+      ops.push('I');
+      row -= 1;
+      d += 1;
+      e -= 1;
+    } else {
+      ops.push('D');
+      d -= 1;
+      e -= 1;
+    }
+  }
+  // the pattern is used up, leading text symbols are free
+  let start = from + d as usize;
+  let mut cigar = String::new();
+  let mut ops = ops.into_iter().rev().peekable();
+  while let Some(op) = ops.next() {
+    let mut run = 1;
+    while ops.next_if_eq(&op).is_some() {
+      run += 1;
+    }
+    cigar.push_str(&format!("{run}{op}"));
+  }
+  Alignment { start, cigar }
+}
+
+/// Diagonals of the pattern against `text[from..to]`, from -min(k, m) to w = to - from.
+struct Diagonals<'a> {
+  lce: &'a GeneralizedSARMQ,
+  from: usize,
+  w: isize,
+  m: isize,
+  neg: isize,
+}
+
+impl<'a> Diagonals<'a> {
+  fn new(lce: &'a GeneralizedSARMQ, from: usize, to: usize, k: usize) -> Self {
+    let m = lce.doc_len(1);
+    // diagonals below -m would end before the text starts
+    Diagonals { lce, from, w: (to - from) as isize, m: m as isize, neg: k.min(m) as isize }
+  }
+
+  fn len(&self) -> usize {
+    (self.neg + self.w + 1) as usize
+  }
+
+  fn diagonal(&self, idx: usize) -> isize {
+    idx as isize - self.neg
+  }
+
+  fn index(&self, d: isize) -> Option<usize> {
+    (-self.neg..=self.w).contains(&d).then(|| (d + self.neg) as usize)
+  }
+
+  /// Whether pattern[row] matches the text at col of the window.
+  fn matches(&self, row: isize, col: isize) -> bool {
+    self.lce.query((0, self.from + col as usize), (1, row as usize)) > 0
+  }
+
+  /// Furthest rows reached with e edits on each diagonal, given the ones with e - 1 edits.
+  fn next_level(&self, e: usize, prev: &[isize]) -> Vec<isize> {
+    let at = |idx: isize| if idx < 0 { NONE } else { prev.get(idx as usize).copied().unwrap_or(NONE) };
+    (0..self.len() as isize).map(|idx| {
+      let d = idx - self.neg;
+      if d < -(e as isize) {
+        return NONE;
+      }
+      // start anywhere in the text for free, substitute, skip a pattern symbol or skip a text symbol
+      let start = if d >= 0 { 0 } else { NONE };
+      let row = start.max(at(idx) + 1).max(at(idx + 1) + 1).max(at(idx - 1)).min(self.m).min(self.w - d);
+      if row < self.m && row + d < self.w {
+        let col = self.from + (row + d) as usize;
+        let slide = self.lce.query((0, col), (1, row as usize)) as isize;
+        row + slide.min(self.w - d - row)
+      } else {
+        row
+      }
+    }).collect()
+  }
+}
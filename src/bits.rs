@@ -0,0 +1,207 @@
+// Bit vectors with rank queries, and the wavelet matrix built of them, for the succinct structures.
+
+use std::iter;
+
+const WORD: usize = 64;
+// words per block with a stored rank
+const BLOCK: usize = 8;
+// blocks per superblock with a stored rank
+const SUPER: usize = 8;
+// ones between the samples for select
+const SELECT_RATE: usize = 4096;
+// bits spanned by the ones between two samples, from which their positions are listed
+const LONG_SPAN: usize = 1 << 24;
+
+/// Bit vector answering the number of ones before any position and the position of the k-th one, both in O(1).
+/// Ranks take a u64 per 4096 bits and a u16 per 512 bits, 3n/64 bits. Select samples every 4096-th one,
+/// and between two samples either searches the at most 2^12 superblocks they span, or reads the positions
+/// listed for them if they span more, which takes at most n/64 bits. Under n/8 extra bits in all.
+pub(crate) struct RankBits {
+  len: usize,
+  words: Vec<u64>,
+  // number of ones before each superblock
+  supers: Vec<u64>,
+  // number of ones before each block, since the start of its superblock
+  blocks: Vec<u16>,
+  // position of every SELECT_RATE-th one, and the length past the last one
+  select_samples: Vec<u64>,
+  // for the samples spanning LONG_SPAN bits or more, the number of such ones before
+  select_long: Vec<u32>,
+  // positions of the ones between those samples
+  select_listed: Vec<u64>,
+}
+
+/// Bits packed into words as they are pushed, for the sequences which aren't at hand as an iterator.
+#[derive(Default)]
+pub(crate) struct BitsBuilder {
+  len: usize,
+  words: Vec<u64>,
+}
+
+impl BitsBuilder {
+  pub fn push(&mut self, bit: bool) {
+    if self.len.is_multiple_of(WORD) {
+      self.words.push(0);
+    }
+    self.words[self.len / WORD] |= (bit as u64) << (self.len % WORD);
+    self.len += 1;
+  }
+
+  pub fn build(self) -> RankBits {
+    RankBits::from_words(self.len, self.words)
+  }
+}
+
+impl RankBits {
+  pub fn from_bits(bits: impl IntoIterator<Item = bool>) -> Self {
+    let mut builder = BitsBuilder::default();
+    bits.into_iter().for_each(|bit| builder.push(bit));
+    builder.build()
+  }
+
+  fn from_words(len: usize, mut words: Vec<u64>) -> Self {
+    // pushed one by one, they may have left room for twice as many
+    words.shrink_to_fit();
+    // a block past the end as well, for the rank of the length
+    let block_count = words.len() / BLOCK + 1;
+    let mut supers = Vec::with_capacity(block_count.div_ceil(SUPER));
+    let mut blocks = Vec::with_capacity(block_count);
+    let mut ones = 0;
+    for block in 0..block_count {
+      if block.is_multiple_of(SUPER) {
+        supers.push(ones as u64);
+      }
+      blocks.push((ones - *supers.last().unwrap() as usize) as u16);
+      ones += words[block * BLOCK..words.len().min((block + 1) * BLOCK)].iter().map(|x| x.count_ones() as usize).sum::<usize>();
+    }
+
+    let positions = || words.iter().enumerate().flat_map(|(w, &word)| {
+      iter::successors(Some(word).filter(|&x| x != 0), |&x| Some(x & (x - 1)).filter(|&x| x != 0))
+        .map(move |x| w * WORD + x.trailing_zeros() as usize)
+    });
+    let mut select_samples: Vec<u64> = positions().step_by(SELECT_RATE).map(|p| p as u64).collect();
+    select_samples.push(len as u64);
+    let long = |s: usize| (select_samples[s + 1] - select_samples[s]) as usize >= LONG_SPAN;
+    let select_long = (0..select_samples.len() - 1).scan(0, |before, s| {
+      let count = *before;
+      *before += long(s) as u32;
+      Some(count)
+    }).collect();
+    let select_listed = positions().enumerate().filter(|&(k, _)| long(k / SELECT_RATE)).map(|(_, p)| p as u64).collect();
+    RankBits { len, words, supers, blocks, select_samples, select_long, select_listed }
+  }
+
+  pub fn get(&self, i: usize) -> bool {
+    assert!(i < self.len, "Bit {i} out of range 0..{}.", self.len);
+    self.words[i / WORD] >> (i % WORD) & 1 == 1
+  }
+
+  /// Number of ones in 0..i.
+  pub fn rank1(&self, i: usize) -> usize {
+    assert!(i <= self.len, "Rank {i} out of range 0..={}.", self.len);
+    let (w, offset) = (i / WORD, i % WORD);
+    let block = w / BLOCK;
+    let whole: usize = self.words[block * BLOCK..w].iter().map(|x| x.count_ones() as usize).sum();
+    let part = if offset > 0 { (self.words[w] & ((1 << offset) - 1)).count_ones() as usize } else { 0 };
+    self.block_rank(block) + whole + part
+  }
+
+  // number of ones before the block
+  fn block_rank(&self, block: usize) -> usize {
+    self.supers[block / SUPER] as usize + self.blocks[block] as usize
+  }
+
+  /// Number of zeros in 0..i.
+  pub fn rank0(&self, i: usize) -> usize {
+    i - self.rank1(i)
+  }
+
+  /// Position of the one with k ones before it, listed, or searched for among the superblocks between two samples.
+  pub fn select1(&self, k: usize) -> usize {
+    assert!(k < self.rank1(self.len), "Select {k} out of range 0..{}.", self.rank1(self.len));
+    let s = k / SELECT_RATE;
+    let (from, to) = (self.select_samples[s] as usize, self.select_samples[s + 1] as usize);
+    if to - from >= LONG_SPAN {
+      return self.select_listed[self.select_long[s] as usize * SELECT_RATE + k % SELECT_RATE] as usize;
+    }
+    // the last superblock and then block with at most k ones before it
+    let (lo, hi) = (from / (SUPER * BLOCK * WORD), (to / (SUPER * BLOCK * WORD) + 1).min(self.supers.len()));
+    let sup = lo + self.supers[lo..hi].partition_point(|&ones| ones as usize <= k) - 1;
+    let (lo, hi) = (sup * SUPER, ((sup + 1) * SUPER).min(self.blocks.len()));
+    let block = lo + self.blocks[lo..hi].partition_point(|&ones| self.supers[sup] as usize + ones as usize <= k) - 1;
+    let mut k = k - self.block_rank(block);
+    let mut w = block * BLOCK;
+    while k >= self.words[w].count_ones() as usize {
+      k -= self.words[w].count_ones() as usize;
+      w += 1;
+    }
+    let mut word = self.words[w];
+    for _ in 0..k {
+      word &= word - 1;
+    }
+    w * WORD + word.trailing_zeros() as usize
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn words(&self) -> &[u64] {
+    &self.words
+  }
+}
+
+/// Sequence of integers in 0..2^bits, with access and rank of any value in O(bits).
+/// Level by level from the highest bit, the values are stably sorted by the bits seen so far,
+/// and each level keeps the next bit of every value in that order.
+pub(crate) struct WaveletMatrix {
+  len: usize,
+  // bits of a level, and the number of zeros among them
+  levels: Vec<(RankBits, usize)>,
+}
+
+impl WaveletMatrix {
+  pub fn new(mut values: Vec<usize>, bits: usize) -> Self {
+    let len = values.len();
+    let mut levels = Vec::with_capacity(bits);
+    for level in (0..bits).rev() {
+      let bit = |x: usize| x >> level & 1 == 1;
+      let rank = RankBits::from_bits(values.iter().map(|&x| bit(x)));
+      let zeros = rank.rank0(len);
+      let (mut next, ones): (Vec<usize>, Vec<usize>) = values.into_iter().partition(|&x| ! bit(x));
+      next.extend(ones);
+      values = next;
+      levels.push((rank, zeros));
+    }
+    WaveletMatrix { len, levels }
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn get(&self, mut i: usize) -> usize {
+    let mut x = 0;
+    for (rank, zeros) in &self.levels {
+      let bit = rank.get(i);
+      x = x << 1 | bit as usize;
+      i = if bit { zeros + rank.rank1(i) } else { rank.rank0(i) };
+    }
+    x
+  }
+
+  /// Number of occurrences of x in 0..i.
+  pub fn rank(&self, x: usize, i: usize) -> usize {
+    let bits = self.levels.len();
+    // positions of the values with the same bits so far as x, in the order of the level
+    let (mut lo, mut hi) = (0, i);
+    for (level, (rank, zeros)) in self.levels.iter().enumerate() {
+      if x >> (bits - 1 - level) & 1 == 1 {
+        (lo, hi) = (zeros + rank.rank1(lo), zeros + rank.rank1(hi));
+      } else {
+        (lo, hi) = (rank.rank0(lo), rank.rank0(hi));
+      }
+    }
+    hi - lo
+  }
+}
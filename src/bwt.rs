@@ -0,0 +1,78 @@
+// Burrows-Wheeler transform, read off the suffix array, and its inverse.
+// The sequence is taken to end with a sentinel smaller than any symbol, which makes the transform invertible.
+// The sentinel isn't a symbol of T, so it is left out of the transform and its position is kept instead.
+
+use std::hash::Hash;
+
+use crate::index::IndexType;
+use crate::{alphabet, sa};
+
+/// Burrows-Wheeler transform of xs followed by the sentinel: last symbols of its sorted rotations.
+/// The sentinel is removed, `primary_index` is where it was, so the transform with the sentinel
+/// is `symbols[..primary_index] + [sentinel] + symbols[primary_index..]`, of length n + 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bwt<T> {
+  pub symbols: Vec<T>,
+  pub primary_index: usize,
+}
+
+/// Burrows-Wheeler transform of a sequence of any ordered symbols, see `Bwt`.
+/// O(n + sigma log sigma) time.
+pub fn bwt<T: Ord + Hash + Clone>(xs: &[T]) -> Bwt<T> {
+  let sa = sa::SA::<usize>::create_suffix_array(&alphabet::compact(xs));
+  bwt_from_sa(xs, &sa.sa)
+}
+
+/// Like `bwt`, with the suffix array of xs already at hand, e.g. `SARMQ::sa`.
+/// O(n) time.
+pub fn bwt_from_sa<T: Clone, I: IndexType>(xs: &[T], sa: &[I]) -> Bwt<T> {
+  assert_eq!(xs.len(), sa.len(), "Suffix array of a different length than the sequence.");
+  // with the sentinel, the first rotation starts with it, and the whole sequence ends in it
+  let mut symbols = Vec::with_capacity(xs.len());
+  let mut primary_index = 0;
+  symbols.extend(xs.last().cloned());
+  for (rank, i) in sa.iter().enumerate() {
+    match i.to_usize() {
+      0 => primary_index = rank + 1,
+      i => symbols.push(xs[i - 1].clone()),
+    }
+  }
+  Bwt { symbols, primary_index }
+}
+
+/// Recovers the sequence from its transform, see `Bwt`, by following the last-to-first mapping.
+/// Panics if primary_index is larger than the length, or the symbols aren't a transform with the sentinel there.
+/// O(n + sigma log sigma) time.
+pub fn inverse_bwt<T: Ord + Hash + Clone>(symbols: &[T], primary_index: usize) -> Vec<T> {
+  let n = symbols.len();
+  assert!(primary_index <= n, "Primary index {primary_index} out of range 0..={n}.");
+  let ranks = alphabet::compact(symbols);
+  // rotations starting with each symbol come after the one starting with the sentinel, and ones with smaller symbols
+  let mut first = vec![0; n + 1];
+  ranks.iter().for_each(|&c| first[c + 1] += 1);
+  first[0] = 1;
+  for c in 0..n {
+    first[c + 1] += first[c];
+  }
+  // row of the rotation starting with each symbol of the transform
+  let lf: Vec<usize> = ranks.iter().map(|&c| {
+    first[c] += 1;
+    first[c] - 1
+  }).collect();
+  // symbols of the transform, without the sentinel, by their row
+  let at = |row: usize| match row.cmp(&primary_index) {
+    std::cmp::Ordering::Less => row,
+    std::cmp::Ordering::Equal => panic!("Not a Burrows-Wheeler transform with the sentinel at {primary_index}."),
+    std::cmp::Ordering::Greater => row - 1,
+  };
+  // the first row starts with the sentinel, so ends with the last symbol, and so on backwards
+  let mut xs = Vec::with_capacity(n);
+  let mut row = 0;
+  for _ in 0..n {
+    let j = at(row);
+    xs.push(symbols[j].clone());
+    row = lf[j];
+  }
+  xs.reverse();
+  xs
+}
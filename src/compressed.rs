@@ -0,0 +1,100 @@
+// SARMQ in compressed space: the suffix array and its inverse come from an FM-index,
+// the lcp array is kept in text order as a bit vector (Sadakane), and the range minima over it
+// come from the parentheses of its Cartesian tree, so no array of n words is left.
+
+use crate::bits::RankBits;
+use crate::fm_index::FmIndex;
+use crate::index::IndexType;
+use crate::rmq::SuccinctRMQ;
+use crate::{check_query, lcp_query, sa, SarmqError};
+
+/// Answers the same queries as `SARMQ` in O(rate log sigma) time, keeping
+/// n log sigma bits of the Burrows-Wheeler transform, 2n / rate words of samples of the suffix array and its inverse,
+/// and about 2n bits for the lcp array and 2.3n for its range minima.
+pub struct CompressedSARMQ {
+  fm: FmIndex<usize>,
+  // bit plcp[p] + 2p set for every position p, where plcp[p] is the lcp at the rank of the suffix p;
+  // the lcp drops by at most one from one position to the next, so these are increasing and below 2n
+  plcp: RankBits,
+  // leftmost minima of ranges of the lcp array in the suffix array order
+  lcp_rmq: SuccinctRMQ,
+}
+
+impl CompressedSARMQ {
+
+  /// Builds the structure over a sequence of integers in 0..n range, of any of the index types, keeping every rate-th position
+  /// of the suffix array and its inverse, so that the queries take up to 3 rate steps.
+  /// O(n log sigma) time, and O(n) words of memory while building.
+  pub fn create_compressed_sarmq(a: &[impl IndexType], rate: usize) -> Self {
+    let n = a.len();
+    let sa = sa::SA::<usize>::create_suffix_array(a);
+    let lcp = sa::lcp(a, &sa);
+    let lcp_rmq = SuccinctRMQ::create_succinct_rmq(&lcp);
+    let mut ones = (0..n).map(|p| lcp[sa.sa_inverse[p]] + 2 * p).peekable();
+    let plcp = RankBits::from_bits((0..2 * n).map(|k| ones.next_if_eq(&k).is_some()));
+    // rank of a symbol among the distinct ones, from a bit per possible value
+    let mut present = vec![false; n];
+    a.iter().for_each(|x| present[x.to_usize()] = true);
+    let symbols = (0..n).filter(|&x| present[x]).collect();
+    let present = RankBits::from_bits(present);
+    let fm = FmIndex::from_suffix_array(symbols, |i| present.rank1(a[i].to_usize()), &sa.sa, rate);
+    CompressedSARMQ { fm, plcp, lcp_rmq }
+  }
+
+  /// Like `create_compressed_sarmq`, but checks that the integers are in 0..n range.
+  pub fn try_create_compressed_sarmq(a: &[impl IndexType], rate: usize) -> Result<Self, SarmqError> {
+    sa::check_alphabet(a)?;
+    Ok(Self::create_compressed_sarmq(a, rate))
+  }
+
+  /// Returns the length of the longest common prefix of the suffixes starting at the given indices,
+  /// in 0..=n range, see `SARMQ::query`.
+  pub fn query(&self, i: usize, j: usize) -> usize {
+    lcp_query(self.len(), i, j, |i| self.sa_inverse(i), |rank| self.lcp(rank), |l, r| self.lcp_rmq.query(l, r))
+  }
+
+  /// Like `query`, but returns None for indices out of 0..=n range.
+  pub fn checked_query(&self, i: usize, j: usize) -> Option<usize> {
+    self.try_query(i, j).ok()
+  }
+
+  /// Like `query`, but reports indices out of 0..=n range as an error.
+  pub fn try_query(&self, i: usize, j: usize) -> Result<usize, SarmqError> {
+    check_query(self.len(), i, j).map(|()| self.query(i, j))
+  }
+
+  /// Length of the indexed sequence.
+  pub fn len(&self) -> usize {
+    self.fm.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.fm.is_empty()
+  }
+
+  /// Starting index of the suffix of the given rank, in O(rate log sigma) time.
+  pub fn sa(&self, rank: usize) -> usize {
+    assert!(rank < self.len(), "Rank {rank} out of range 0..{}.", self.len());
+    // row 0 is the empty suffix
+    self.fm.locate_row(rank + 1)
+  }
+
+  /// Rank of the suffix starting at index i, in O(rate log sigma) time.
+  pub fn sa_inverse(&self, i: usize) -> usize {
+    assert!(i < self.len(), "Index {i} out of range 0..{}.", self.len());
+    self.fm.row_of(i) - 1
+  }
+
+  /// Length of the longest common prefix of the suffixes of ranks rank and rank + 1, 0 for the last one,
+  /// as `SARMQ::lcp`, in O(rate log sigma + log n) time.
+  pub fn lcp(&self, rank: usize) -> usize {
+    let p = self.sa(rank);
+    self.plcp.select1(p) - 2 * p
+  }
+
+  /// Every rate-th position of the suffix array and its inverse is kept.
+  pub fn rate(&self) -> usize {
+    self.fm.rate()
+  }
+
+}
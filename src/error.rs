@@ -0,0 +1,81 @@
+// Errors reported by the fallible constructors, instead of panicking deep inside the construction,
+// and by loading of saved structures.
+
+use std::{fmt, io};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SarmqError {
+  /// The suffix array construction assumes symbols in 0..n range, `symbol` at `index` is not.
+  SymbolOutOfRange { index: usize, symbol: usize, n: usize },
+  /// Queried `index` is not a suffix of the sequence of length `len`, that is not in 0..=len range.
+  IndexOutOfRange { index: usize, len: usize },
+  /// The sequence of length `len` is too long for the chosen index type, which supports lengths up to `max_len`.
+  LengthOverflow { len: usize, max_len: usize },
+  /// The input isn't a rooted tree: `node` is a second root, or lies on a cycle, or can't be reached from the root.
+  NotATree { node: usize },
+}
+
+impl fmt::Display for SarmqError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SarmqError::SymbolOutOfRange { index, symbol, n } =>
+        write!(f, "symbol {symbol} at index {index} is out of the alphabet range 0..{n}"),
+      SarmqError::IndexOutOfRange { index, len } =>
+        write!(f, "index {index} is out of range 0..={len}"),
+      SarmqError::LengthOverflow { len, max_len } =>
+        write!(f, "sequence of length {len} is too long for the index type, which supports lengths up to {max_len}"),
+      SarmqError::NotATree { node } =>
+        write!(f, "not a rooted tree, at node {node}"),
+    }
+  }
+}
+
+impl std::error::Error for SarmqError {}
+
+/// Errors of loading a saved structure.
+#[derive(Debug)]
+pub enum PersistError {
+  Io(io::Error),
+  /// The file doesn't start with the expected magic bytes, so it isn't a saved structure of this kind.
+  BadMagic,
+  /// The file was written in a format version this build can't read.
+  UnsupportedVersion { version: u32 },
+  /// The file stores indices `found` bytes wide, but `expected` bytes were requested.
+  IndexWidthMismatch { expected: usize, found: usize },
+  /// The checksum of the contents doesn't match the stored one.
+  ChecksumMismatch,
+  /// The contents are inconsistent, e.g. an array of a wrong length or an index out of range.
+  Corrupted(&'static str),
+  /// The buffer can't be queried in place, e.g. because it isn't aligned.
+  Unsupported(&'static str),
+}
+
+impl fmt::Display for PersistError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      PersistError::Io(e) => write!(f, "i/o error: {e}"),
+      PersistError::BadMagic => write!(f, "not a saved structure of the expected kind"),
+      PersistError::UnsupportedVersion { version } => write!(f, "unsupported format version {version}"),
+      PersistError::IndexWidthMismatch { expected, found } =>
+        write!(f, "saved with {found} byte indices, but {expected} byte indices were requested"),
+      PersistError::ChecksumMismatch => write!(f, "checksum mismatch, the file is corrupted"),
+      PersistError::Corrupted(what) => write!(f, "corrupted file: {what}"),
+      PersistError::Unsupported(what) => write!(f, "can't be used in place: {what}"),
+    }
+  }
+}
+
+impl std::error::Error for PersistError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      PersistError::Io(e) => Some(e),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for PersistError {
+  fn from(e: io::Error) -> Self {
+    PersistError::Io(e)
+  }
+}
@@ -0,0 +1,170 @@
+// FM-index: the Burrows-Wheeler transform with rank queries over it, in a wavelet matrix,
+// which finds the range of suffixes starting with a pattern symbol by symbol from its end (backward search),
+// and samples of the suffix array and its inverse, for the positions of the occurrences and substrings of the text.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+use std::ops::Range;
+
+use crate::bits::{RankBits, WaveletMatrix};
+use crate::{alphabet, sa};
+
+/// Compressed full-text index of a sequence, for counting, locating and extracting without the sequence.
+/// Takes n log sigma bits for the transform, and 2n / rate words for the samples.
+/// Rows are the sorted rotations of the sequence followed by a sentinel, row 0 starts with the sentinel.
+pub struct FmIndex<T> {
+  // distinct symbols, a symbol is coded as its index + 1, the sentinel as 0
+  symbols: Vec<T>,
+  // transform with the sentinel, coded
+  bwt: WaveletMatrix,
+  // number of symbols of the text and the sentinel smaller than each code
+  c: Vec<usize>,
+  rate: usize,
+  // rows holding a sample of the suffix array, that is of a position divisible by rate, and their positions
+  sampled: RankBits,
+  sa_samples: Vec<usize>,
+  // row of each position divisible by rate
+  isa_samples: Vec<usize>,
+}
+
+impl<T: Ord + Hash + Clone> FmIndex<T> {
+
+  /// Builds the index with every rate-th position of the suffix array kept,
+  /// so that `locate` takes up to rate steps per occurrence, and `extract` up to rate extra steps.
+  /// O(n log sigma + sigma log sigma) time.
+  pub fn create_fm_index(xs: &[T], rate: usize) -> Self {
+    let ranks = alphabet::compact(xs);
+    let sa = sa::SA::<usize>::create_suffix_array(&ranks).sa;
+    let mut symbols: Vec<T> = xs.iter().collect::<HashSet<&T>>().into_iter().cloned().collect();
+    symbols.sort_unstable();
+    Self::from_suffix_array(symbols, |i| ranks[i], &sa, rate)
+  }
+
+  /// Builds the index given the distinct symbols in order, the rank among them of the symbol at each position,
+  /// and the suffix array.
+  pub(crate) fn from_suffix_array(symbols: Vec<T>, rank_at: impl Fn(usize) -> usize, sa: &[usize], rate: usize) -> Self {
+    assert!(rate > 0, "Sampling rate has to be positive.");
+    let n = sa.len();
+    let sigma = symbols.len() + 1;
+    // suffix array with the sentinel, the empty suffix first
+    let sa_at = |row: usize| if row == 0 { n } else { sa[row - 1] };
+    let code_before = |i: usize| if i == 0 { 0 } else { rank_at(i - 1) + 1 };
+    let bwt: Vec<usize> = (0..=n).map(|row| code_before(sa_at(row))).collect();
+
+    let mut c = vec![0; sigma + 1];
+    bwt.iter().for_each(|&x| c[x + 1] += 1);
+    for x in 0..sigma {
+      c[x + 1] += c[x];
+    }
+    let sampled = RankBits::from_bits((0..=n).map(|row| sa_at(row).is_multiple_of(rate)));
+    let sa_samples = (0..=n).map(sa_at).filter(|i| i.is_multiple_of(rate)).collect();
+    let mut isa_samples = vec![0; n / rate + 1];
+    for row in 0..=n {
+      if sa_at(row).is_multiple_of(rate) {
+        isa_samples[sa_at(row) / rate] = row;
+      }
+    }
+    let bits = (usize::BITS - (sigma - 1).leading_zeros()) as usize;
+    FmIndex { symbols, bwt: WaveletMatrix::new(bwt, bits), c, rate, sampled, sa_samples, isa_samples }
+  }
+
+  /// Length of the indexed sequence.
+  pub fn len(&self) -> usize {
+    self.bwt.len() - 1
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Rows of the rotations starting with pattern, found backwards from its last symbol.
+  /// O(m log sigma) time.
+  pub fn sa_range(&self, pattern: &[T]) -> Range<usize> {
+    let mut range = 0..self.bwt.len();
+    for x in pattern.iter().rev() {
+      let Ok(k) = self.symbols.binary_search(x) else { return 0..0 };
+      let code = k + 1;
+      range = self.c[code] + self.bwt.rank(code, range.start) .. self.c[code] + self.bwt.rank(code, range.end);
+      if range.is_empty() {
+        return 0..0;
+      }
+    }
+    range
+  }
+
+  /// Number of occurrences of pattern.
+  /// O(m log sigma) time.
+  pub fn count(&self, pattern: &[T]) -> usize {
+    // the empty pattern would match the sentinel row as well
+    if pattern.is_empty() { self.len() } else { self.sa_range(pattern).len() }
+  }
+
+  /// Starting indices of the occurrences of pattern, in the suffix array order.
+  /// O(m log sigma + occ rate log sigma) time.
+  pub fn locate(&self, pattern: &[T]) -> Vec<usize> {
+    let range = if pattern.is_empty() { 1..self.bwt.len() } else { self.sa_range(pattern) };
+    range.map(|row| self.locate_row(row)).collect()
+  }
+
+  /// Position of the suffix of the row, found by stepping back in the text until a sampled one.
+  pub(crate) fn locate_row(&self, mut row: usize) -> usize {
+    let mut steps = 0;
+    while ! self.sampled.get(row) {
+      row = self.lf(row);
+      steps += 1;
+    }
+    self.sa_samples[self.sampled.rank1(row)] + steps
+  }
+
+  /// Row of the suffix starting at i, for i in 0..=n, found by stepping back from the first sampled position after it.
+  pub(crate) fn row_of(&self, i: usize) -> usize {
+    let (mut j, mut row) = self.sample_after(i);
+    while j > i {
+      row = self.lf(row);
+      j -= 1;
+    }
+    row
+  }
+
+  // the first sampled position at or after i and its row, the end of the sequence when no multiple of rate is left
+  fn sample_after(&self, i: usize) -> (usize, usize) {
+    let n = self.len();
+    match i.div_ceil(self.rate) {
+      k if k * self.rate <= n => (k * self.rate, self.isa_samples[k]),
+      _ => (n, 0),
+    }
+  }
+
+  /// Row of the rotation starting one symbol earlier, that is with the last symbol of the row.
+  fn lf(&self, row: usize) -> usize {
+    let code = self.bwt.get(row);
+    self.c[code] + self.bwt.rank(code, row)
+  }
+
+  /// Substring of the indexed sequence, decoded backwards from the first sampled position after it.
+  /// O((len + rate) log sigma) time.
+  pub fn extract(&self, range: Range<usize>) -> Vec<T> {
+    let n = self.len();
+    assert!(range.start <= range.end && range.end <= n, "Range {range:?} out of 0..{n}.");
+    let (mut i, mut row) = self.sample_after(range.end);
+    let mut xs = Vec::with_capacity(range.len());
+    while i > range.start {
+      // the row ends with the symbol before its suffix
+      let code = self.bwt.get(row);
+      if i <= range.end {
+        xs.push(self.symbols[code - 1].clone());
+      }
+      row = self.c[code] + self.bwt.rank(code, row);
+      i -= 1;
+    }
+    xs.reverse();
+    xs
+  }
+
+  /// Every rate-th position of the suffix array is kept.
+  pub fn rate(&self) -> usize {
+    self.rate
+  }
+
+}
+
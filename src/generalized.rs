@@ -0,0 +1,155 @@
+// SARMQ over several sequences at once, answering lcp queries between suffixes of different sequences.
+// The sequences are joined with a unique separator after each one, so no common prefix runs across them.
+
+use std::hash::Hash;
+
+use crate::index::IndexType;
+use crate::{alphabet, build_sarmq, check_len, SarmqError, SARMQ};
+
+/// Answers in O(1) the length of the longest common prefix of `docs[a][i..]` and `docs[b][j..]`.
+/// Positions are (doc, i) pairs, with i in 0..=len of the doc, where len stands for the empty suffix.
+pub struct GeneralizedSARMQ<I = usize> {
+  sarmq: SARMQ<I>,
+  // start of each doc in the joined sequence, followed by its length
+  starts: Vec<usize>,
+}
+
+/// Substring common to several docs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommonSubstring {
+  pub len: usize,
+  /// (doc, i) positions of all of its occurrences, in increasing order.
+  pub occurrences: Vec<(usize, usize)>,
+}
+
+impl GeneralizedSARMQ {
+
+  /// Builds the structure over docs of any ordered symbols, in O(N + sigma log sigma) time for their total length N.
+  pub fn create_generalized_sarmq<T: Ord + Hash>(docs: &[impl AsRef<[T]>]) -> Self {
+    Self::build(docs)
+  }
+
+}
+
+impl<I: IndexType> GeneralizedSARMQ<I> {
+
+  /// Like `create_generalized_sarmq`, but storing indices as I and reporting a too long input,
+  /// see `try_create_sarmq_with_index`. The separators count into the length.
+  pub fn try_create_with_index<T: Ord + Hash>(docs: &[impl AsRef<[T]>]) -> Result<Self, SarmqError> {
+    let len = docs.iter().map(|doc| doc.as_ref().len() + 1).sum();
+    check_len(len, I::MAX / 2)?;
+    Ok(Self::build(docs))
+  }
+
+  fn build<T: Ord + Hash>(docs: &[impl AsRef<[T]>]) -> Self {
+    let m = docs.len();
+    let symbols: Vec<&T> = docs.iter().flat_map(|doc| doc.as_ref()).collect();
+    let mut ranks = alphabet::compact(&symbols).into_iter();
+    // separators are 0..m, below the symbols, which are shifted by m; all in 0..n range of the joined sequence
+    let mut joined = Vec::with_capacity(symbols.len() + m);
+    let mut starts = Vec::with_capacity(m + 1);
+    for (d, doc) in docs.iter().enumerate() {
+      starts.push(joined.len());
+      joined.extend(ranks.by_ref().take(doc.as_ref().len()).map(|r| r + m));
+      joined.push(d);
+    }
+    starts.push(joined.len());
+    GeneralizedSARMQ { sarmq: build_sarmq(&joined), starts }
+  }
+
+  /// Length of the longest common prefix of the suffixes `docs[a.0][a.1..]` and `docs[b.0][b.1..]`.
+  /// Panics for a doc out of range or an index larger than the length of its doc.
+  pub fn query(&self, a: (usize, usize), b: (usize, usize)) -> usize {
+    let (i, j) = (self.position(a), self.position(b));
+    if i == j {
+      self.doc_len(a.0) - a.1
+    } else {
+      // different positions can't both match their unique separator at the same distance
+      self.sarmq.query(i, j)
+    }
+  }
+
+  /// Number of the docs.
+  pub fn docs(&self) -> usize {
+    self.starts.len() - 1
+  }
+
+  /// Length of the given doc.
+  pub fn doc_len(&self, doc: usize) -> usize {
+    self.starts[doc + 1] - self.starts[doc] - 1
+  }
+
+  /// Index in the joined sequence of the given (doc, i) position, where i = len of the doc is its separator.
+  pub fn position(&self, (doc, i): (usize, usize)) -> usize {
+    assert!(doc < self.docs(), "Doc {doc} out of range 0..{}.", self.docs());
+    assert!(i <= self.doc_len(doc), "Index {i} out of range 0..={} of doc {doc}.", self.doc_len(doc));
+    self.starts[doc] + i
+  }
+
+  /// (doc, i) position of an index in the joined sequence, the inverse of `position`.
+  pub fn doc_position(&self, p: usize) -> (usize, usize) {
+    assert!(p < self.sarmq.len(), "Index {p} out of range 0..{}.", self.sarmq.len());
+    let doc = self.starts.partition_point(|&s| s <= p) - 1;
+    (doc, p - self.starts[doc])
+  }
+
+  /// Longest substring occurring in at least k of the docs, e.g. in all of them for k = `docs()`.
+  /// None if there is no such non-empty substring. Of several ones of the same length, the smallest one is reported.
+  /// Slides a window over the suffix array, keeping it just large enough to cover k docs,
+  /// and takes the lcp of its ends with a query. O(N log m) time, O(m) extra space.
+  pub fn longest_common_substring(&self, k: usize) -> Option<CommonSubstring> {
+    assert!(k > 0, "A substring has to occur in at least one doc.");
+    let m = self.docs();
+    if k > m {
+      return None;
+    }
+    let sa = self.sarmq.sa();
+    let doc = |rank: usize| self.doc_position(sa[rank].to_usize()).0;
+    // suffixes starting with a separator come first, being the least symbols
+    let (mut counts, mut covered) = (vec![0; m], 0);
+    let (mut lo, mut best) = (m, (0, m, m));
+    for hi in m..sa.len() {
+      counts[doc(hi)] += 1;
+      if counts[doc(hi)] == 1 {
+        covered += 1;
+      }
+      while covered >= k {
+        let len = if lo == hi {
+          let (d, i) = self.doc_position(sa[lo].to_usize());
+          self.doc_len(d) - i
+        } else {
+          self.sarmq.query(sa[lo].to_usize(), sa[hi].to_usize())
+        };
+        if len > best.0 {
+          best = (len, lo, hi);
+        }
+        counts[doc(lo)] -= 1;
+        if counts[doc(lo)] == 0 {
+          covered -= 1;
+        }
+        lo += 1;
+      }
+    }
+    let (len, mut lo, mut hi) = best;
+    if len == 0 {
+      return None;
+    }
+    // all the suffixes sharing the substring, besides the window
+    let lcp = self.sarmq.lcp();
+    while lo > m && lcp[lo - 1].to_usize() >= len {
+      lo -= 1;
+    }
+    while lcp[hi].to_usize() >= len {
+      hi += 1;
+    }
+    let mut occurrences: Vec<(usize, usize)> = sa[lo..=hi].iter().map(|x| self.doc_position(x.to_usize())).collect();
+    occurrences.sort_unstable();
+    Some(CommonSubstring { len, occurrences })
+  }
+
+  /// Structure over the joined sequence, docs followed by their separators, see `position`.
+  pub fn sarmq(&self) -> &SARMQ<I> {
+    &self.sarmq
+  }
+
+}
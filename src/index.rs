@@ -0,0 +1,47 @@
+// Integer types used for storing indices, so that large inputs can be indexed with less memory.
+
+use crate::SarmqError;
+
+mod sealed {
+  pub trait Sealed {}
+}
+
+/// Unsigned integer type in which the structures store their indices and lengths.
+/// u32 halves the memory of the usize default on 64-bit machines.
+/// Implemented for u32, u64 and usize only, saved structures are read in place as these.
+pub trait IndexType: sealed::Sealed + Copy + Ord + Default + std::fmt::Debug + Send + Sync + 'static {
+  /// Largest value representable by the type.
+  const MAX: usize;
+  /// Converts x, which has to be at most MAX, checked in debug builds only.
+  fn from_usize(x: usize) -> Self;
+  fn to_usize(self) -> usize;
+}
+
+macro_rules! impl_index_type {
+  ($($t:ty),*) => {$(
+    impl sealed::Sealed for $t {}
+    impl IndexType for $t {
+      const MAX: usize = <$t>::MAX as usize;
+      #[inline]
+      fn from_usize(x: usize) -> Self {
+        debug_assert!(x <= <Self as IndexType>::MAX, "{x} does not fit in {}", stringify!($t));
+        x as $t
+      }
+      #[inline]
+      fn to_usize(self) -> usize {
+        self as usize
+      }
+    }
+  )*};
+}
+
+impl_index_type!(u32, u64, usize);
+
+/// Checks that a sequence of length len is not longer than max_len, the longest one the structure can index with I.
+pub(crate) fn check_len(len: usize, max_len: usize) -> Result<(), SarmqError> {
+  if len <= max_len {
+    Ok(())
+  } else {
+    Err(SarmqError::LengthOverflow { len, max_len })
+  }
+}
@@ -0,0 +1,224 @@
+// Tree of the lcp-intervals, standing in for the suffix tree (an enhanced suffix array):
+// the internal nodes are the lcp-intervals, the leaves are the suffixes, all kept in flat arrays.
+
+use std::ops::Range;
+
+use crate::index::IndexType;
+use crate::rmq::{EulerRMQView, RangeMinQuery, RangeMinView};
+use crate::repeats::LcpInterval;
+use crate::{SARMQView, SARMQ};
+
+/// Child of a node of the `LcpIntervalTree`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Child {
+  /// Internal node, by its id.
+  Interval(usize),
+  /// Suffix, by its rank.
+  Leaf(usize),
+}
+
+/// Internal nodes of the suffix tree as lcp-intervals, with the suffixes of the sorted order as leaves.
+/// Nodes are identified by their index in preorder, the root is 0 and stands for the empty string,
+/// a node with lcp l stands for the prefix of length l shared by the suffixes in its range.
+/// Unlike in a suffix tree, the sequence doesn't end with a unique symbol, so a suffix which is a prefix
+/// of another one is a leaf inside of that one's node, and the root can have a single child.
+/// O(n) space and creation time; children, parents and suffix links in O(1) each.
+pub struct LcpIntervalTree<'a, I = usize, R = EulerRMQView<'a, I>> {
+  view: SARMQView<'a, I, R>,
+  // per node, in preorder
+  lcp: Vec<I>,
+  lb: Vec<I>,
+  rb: Vec<I>,
+  parent: Vec<I>,
+  depth: Vec<I>,
+  // first node in preorder starting at each rank, the largest one, or the max value of I
+  first_at: Vec<I>,
+  leaf_parent: Vec<I>,
+  // node whose children meet at each boundary between ranks r and r+1
+  boundary_node: Vec<I>,
+}
+
+impl<'a, I: IndexType, R: RangeMinView<I>> LcpIntervalTree<'a, I, R> {
+
+  /// Builds the tree over the suffix array and the lcp array of the structure.
+  pub fn create_lcp_interval_tree(view: SARMQView<'a, I, R>) -> Self {
+    let n = view.len();
+    let zero = I::from_usize(0);
+    // preorder is by first rank, and larger intervals first,
+    // which a stable counting sort by first rank of the reversed postorder gives
+    let postorder: Vec<LcpInterval> = view.lcp_intervals().collect();
+    let mut starts = vec![0; n + 1];
+    postorder.iter().for_each(|interval| starts[interval.sa_range.start + 1] += 1);
+    for r in 0..n {
+      starts[r + 1] += starts[r];
+    }
+    let mut order = vec![0; postorder.len()];
+    for (k, interval) in postorder.iter().enumerate().rev() {
+      order[starts[interval.sa_range.start]] = k;
+      starts[interval.sa_range.start] += 1;
+    }
+    let root = LcpInterval { lcp: 0, sa_range: 0..n, has_child: false };
+    let nodes: Vec<&LcpInterval> = std::iter::once(&root).chain(order.iter().map(|&k| &postorder[k])).collect();
+    let lcp: Vec<I> = nodes.iter().map(|x| I::from_usize(x.lcp)).collect();
+    let lb: Vec<I> = nodes.iter().map(|x| I::from_usize(x.sa_range.start)).collect();
+    let rb: Vec<I> = nodes.iter().map(|x| I::from_usize(x.sa_range.end)).collect();
+
+    let mut first_at = vec![I::from_usize(I::MAX); n];
+    for (node, x) in nodes.iter().enumerate() {
+      if x.sa_range.start < n && first_at[x.sa_range.start] == I::from_usize(I::MAX) {
+        first_at[x.sa_range.start] = I::from_usize(node);
+      }
+    }
+    // the ancestors of a node are the ones on the stack containing it
+    let mut parent = vec![zero; lcp.len()];
+    let mut depth = vec![zero; lcp.len()];
+    let mut stack: Vec<usize> = vec![];
+    for node in 0..lcp.len() {
+      while let Some(&top) = stack.last() {
+        if rb[node] <= rb[top] {
+          break;
+        }
+        stack.pop();
+      }
+      if let Some(&top) = stack.last() {
+        parent[node] = I::from_usize(top);
+        depth[node] = I::from_usize(depth[top].to_usize() + 1);
+      }
+      stack.push(node);
+    }
+
+    let mut tree = LcpIntervalTree {
+      view, lcp, lb, rb, parent, depth, first_at,
+      leaf_parent: vec![zero; n],
+      boundary_node: vec![zero; n.saturating_sub(1)],
+    };
+    for node in 0..tree.len() {
+      for child in tree.children(node).collect::<Vec<Child>>() {
+        let end = match child {
+          Child::Interval(c) => tree.rb[c].to_usize(),
+          Child::Leaf(r) => {
+            tree.leaf_parent[r] = I::from_usize(node);
+            r + 1
+          }
+        };
+        if end < tree.rb[node].to_usize() {
+          tree.boundary_node[end - 1] = I::from_usize(node);
+        }
+      }
+    }
+    tree
+  }
+
+  /// Number of internal nodes, including the root.
+  pub fn len(&self) -> usize {
+    self.lcp.len()
+  }
+
+  /// Always false, there is at least the root.
+  pub fn is_empty(&self) -> bool {
+    false
+  }
+
+  pub fn root(&self) -> usize {
+    0
+  }
+
+  /// Length of the prefix the suffixes of the node share, its string depth.
+  pub fn lcp(&self, node: usize) -> usize {
+    self.lcp[node].to_usize()
+  }
+
+  /// Ranks of the suffixes of the node.
+  pub fn sa_range(&self, node: usize) -> Range<usize> {
+    self.lb[node].to_usize() .. self.rb[node].to_usize()
+  }
+
+  /// The node as an lcp-interval.
+  pub fn interval(&self, node: usize) -> LcpInterval {
+    let has_child = node + 1 < self.len() && self.lb[node + 1] < self.rb[node];
+    LcpInterval { lcp: self.lcp(node), sa_range: self.sa_range(node), has_child }
+  }
+
+  /// All internal nodes as lcp-intervals, in preorder, so the root first.
+  pub fn intervals(&self) -> impl Iterator<Item = LcpInterval> + '_ {
+    (0..self.len()).map(|node| self.interval(node))
+  }
+
+  /// Children of the node, internal ones and leaves, in the order of their ranks.
+  pub fn children(&self, node: usize) -> impl Iterator<Item = Child> + '_ {
+    let (lb, rb) = (self.lb[node].to_usize(), self.rb[node].to_usize());
+    let mut r = lb;
+    std::iter::from_fn(move || {
+      if r >= rb {
+        return None;
+      }
+      // the largest interval starting at r inside of the node, which at its start is the next one in preorder
+      let interval = if r == lb {
+        Some(node + 1).filter(|&next| next < self.len() && self.lb[next].to_usize() == lb)
+      } else {
+        Some(self.first_at[r]).filter(|&x| x != I::from_usize(I::MAX)).map(|x| x.to_usize())
+      };
+      Some(match interval {
+        Some(c) => {
+          r = self.rb[c].to_usize();
+          Child::Interval(c)
+        }
+        None => {
+          r += 1;
+          Child::Leaf(r - 1)
+        }
+      })
+    })
+  }
+
+  /// Parent of the node, None for the root.
+  pub fn parent(&self, node: usize) -> Option<usize> {
+    (node != 0).then(|| self.parent[node].to_usize())
+  }
+
+  /// Parent of the leaf of the given rank.
+  pub fn leaf_parent(&self, rank: usize) -> usize {
+    self.leaf_parent[rank].to_usize()
+  }
+
+  /// Number of ancestors of the node, 0 for the root.
+  pub fn depth(&self, node: usize) -> usize {
+    self.depth[node].to_usize()
+  }
+
+  /// Node standing for the string of the node without its first symbol, None for the root.
+  /// It holds the suffixes of the node, each one shorter by a symbol, so it is the lowest common ancestor
+  /// of its first and last of them, found with the range minimum query on lcp of the view.
+  pub fn suffix_link(&self, node: usize) -> Option<usize> {
+    match self.lcp(node) {
+      0 => None,
+      1 => Some(0),
+      _ => {
+        let (sa, sa_inverse) = (self.view.sa(), self.view.sa_inverse());
+        let first = sa_inverse[sa[self.lb[node].to_usize()].to_usize() + 1].to_usize();
+        let last = sa_inverse[sa[self.rb[node].to_usize() - 1].to_usize() + 1].to_usize();
+        let boundary = self.view.rmq.query(self.view.lcp, first, last - 1);
+        Some(self.boundary_node[boundary].to_usize())
+      }
+    }
+  }
+
+}
+
+impl<'a, I: IndexType, R: RangeMinView<I>> SARMQView<'a, I, R> {
+
+  /// Tree of the lcp-intervals, see `LcpIntervalTree`.
+  pub fn lcp_interval_tree(&self) -> LcpIntervalTree<'a, I, R> {
+    LcpIntervalTree::create_lcp_interval_tree(*self)
+  }
+
+}
+
+impl<I: IndexType, R: RangeMinQuery<I>> SARMQ<I, R> {
+
+  /// Tree of the lcp-intervals, see `LcpIntervalTree`.
+  pub fn lcp_interval_tree(&self) -> LcpIntervalTree<'_, I, R::View<'_>> {
+    self.view().lcp_interval_tree()
+  }
+
+}
@@ -0,0 +1,138 @@
+// Lowest common ancestors in any rooted tree (Bender, Farach-Colton): the lowest common ancestor of two nodes
+// is the shallowest node on the Euler tour between their first visits, and depths along the tour differ by 1.
+
+use crate::rmq::RMQ;
+use crate::SarmqError;
+
+/// Answers lowest common ancestor queries on a rooted tree with nodes 0..n.
+/// O(n) space and creation time, O(1) query time.
+pub struct Lca {
+  depth: Vec<usize>,
+  first_occ: Vec<usize>, // position of each node in the euler tour
+  euler: Vec<usize>, // nodes along the euler tour, visited when entering and after each child
+  rmq: RMQ,
+}
+
+impl Lca {
+
+  /// Builds from the parent of each node, None for the root.
+  pub fn from_parents(parents: &[Option<usize>]) -> Result<Self, SarmqError> {
+    let n = parents.len();
+    let mut root = None;
+    // children of each node, gathered in one array
+    let mut starts = vec![0; n + 1];
+    for (node, &parent) in parents.iter().enumerate() {
+      match parent {
+        None if root.is_some() => return Err(SarmqError::NotATree { node }),
+        None => root = Some(node),
+        Some(p) if p >= n => return Err(SarmqError::IndexOutOfRange { index: p, len: n }),
+        Some(p) => starts[p + 1] += 1,
+      }
+    }
+    let root = match root {
+      // with no root, every node is on a cycle
+      None if n > 0 => return Err(SarmqError::NotATree { node: 0 }),
+      None => return Self::try_build(0, 0, |_| &[]),
+      Some(root) => root,
+    };
+    // one root, so the other n - 1 nodes have a parent
+    for i in 0..n {
+      starts[i + 1] += starts[i];
+    }
+    let mut children = vec![0; n.saturating_sub(1)];
+    let mut next = starts.clone();
+    for (node, &parent) in parents.iter().enumerate() {
+      if let Some(p) = parent {
+        children[next[p]] = node;
+        next[p] += 1;
+      }
+    }
+    Self::try_build(n, root, |u| &children[starts[u]..starts[u + 1]])
+  }
+
+  /// Builds from the neighbours of each node, either the children alone or also the parent, and the root.
+  pub fn from_adjacency(root: usize, adjacency: &[impl AsRef<[usize]>]) -> Result<Self, SarmqError> {
+    let n = adjacency.len();
+    if root >= n {
+      return Err(SarmqError::IndexOutOfRange { index: root, len: n });
+    }
+    Self::try_build(n, root, |u| adjacency[u].as_ref())
+  }
+
+  fn try_build<'b>(n: usize, root: usize, neighbours: impl Fn(usize) -> &'b [usize]) -> Result<Self, SarmqError> {
+    let lca = Self::build(n, root, neighbours)?;
+    match lca.euler.len() {
+      len if len == (2 * n).saturating_sub(1) => Ok(lca),
+      // some node wasn't reached from the root
+      _ => Err(SarmqError::NotATree { node: (0..n).find(|&u| lca.first_occ[u] == usize::MAX).unwrap() }),
+    }
+  }
+
+  fn build<'b>(n: usize, root: usize, neighbours: impl Fn(usize) -> &'b [usize]) -> Result<Self, SarmqError> {
+    let mut depth = vec![0; n];
+    let mut first_occ = vec![usize::MAX; n];
+    let mut euler = Vec::with_capacity((2 * n).saturating_sub(1));
+    // node, its parent, and the next of its neighbours to visit
+    let mut stack = vec![];
+    if n > 0 {
+      first_occ[root] = 0;
+      euler.push(root);
+      stack.push((root, usize::MAX, 0));
+    }
+    while let Some((u, parent, i)) = stack.last_mut() {
+      let (u, parent) = (*u, *parent);
+      match neighbours(u).get(*i) {
+        Some(&v) => {
+          *i += 1;
+          if v == parent {
+            continue;
+          }
+          if v >= n {
+            return Err(SarmqError::IndexOutOfRange { index: v, len: n });
+          }
+          if first_occ[v] != usize::MAX {
+            return Err(SarmqError::NotATree { node: v });
+          }
+          depth[v] = depth[u] + 1;
+          first_occ[v] = euler.len();
+          euler.push(v);
+          stack.push((v, u, 0));
+        }
+        None => {
+          stack.pop();
+          if let Some(&(w, _, _)) = stack.last() {
+            euler.push(w);
+          }
+        }
+      }
+    }
+    let rmq = RMQ::create_rmq(euler.iter().map(|&x| depth[x]));
+    Ok(Lca { depth, first_occ, euler, rmq })
+  }
+
+  /// Lowest common ancestor of the nodes u and v.
+  pub fn lca(&self, u: usize, v: usize) -> usize {
+    let (i, j) = (self.first_occ[u], self.first_occ[v]);
+    self.euler[self.rmq.query(i.min(j), i.max(j))]
+  }
+
+  /// Number of edges on the path between the nodes u and v.
+  pub fn distance(&self, u: usize, v: usize) -> usize {
+    self.depth[u] + self.depth[v] - 2 * self.depth[self.lca(u, v)]
+  }
+
+  /// Number of edges from the root to the node.
+  pub fn depth(&self, node: usize) -> usize {
+    self.depth[node]
+  }
+
+  /// Number of nodes.
+  pub fn len(&self) -> usize {
+    self.depth.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.depth.is_empty()
+  }
+
+}
@@ -0,0 +1,76 @@
+// Longest common extensions in both directions, with a second SARMQ over the reversed sequence.
+
+use std::hash::Hash;
+
+use crate::index::IndexType;
+use crate::{alphabet, build_sarmq, check_len, sa, SarmqError, SARMQ};
+
+/// Answers in O(1) both how far two positions agree going right (`lce_forward`) and going left (`lce_backward`).
+/// Positions are in 0..=n in both directions: i stands for the suffix `xs[i..]` going forward,
+/// and for the prefix `xs[..i]` going backward, so position n is the empty suffix and the whole sequence.
+pub struct BidirectionalLCE<I = usize> {
+  forward: SARMQ<I>,
+  backward: SARMQ<I>,
+}
+
+impl BidirectionalLCE {
+
+  /// Builds both directions over a sequence of integers in 0..n range, of any of the index types, in O(n) time.
+  pub fn create_bidirectional_lce(a: &[impl IndexType]) -> Self {
+    Self::build(a)
+  }
+
+  /// Like `create_bidirectional_lce`, for any ordered symbols, which are compacted first.
+  pub fn create_bidirectional_lce_from_symbols<T: Ord + Hash>(a: &[T]) -> Self {
+    Self::build(&alphabet::compact(a))
+  }
+
+}
+
+impl<I: IndexType> BidirectionalLCE<I> {
+
+  /// Like `create_bidirectional_lce`, but storing indices as I and reporting invalid input, see `try_create_sarmq_with_index`.
+  pub fn try_create_with_index(a: &[impl IndexType]) -> Result<Self, SarmqError> {
+    check_len(a.len(), I::MAX / 2)?;
+    sa::check_alphabet(a)?;
+    Ok(Self::build(a))
+  }
+
+  fn build<T: IndexType>(a: &[T]) -> Self {
+    let reversed: Vec<T> = a.iter().rev().copied().collect();
+    BidirectionalLCE { forward: build_sarmq(a), backward: build_sarmq(&reversed) }
+  }
+
+  /// Length of the longest common prefix of `xs[i..]` and `xs[j..]`, for i, j in 0..=n.
+  pub fn lce_forward(&self, i: usize, j: usize) -> usize {
+    self.forward.query(i, j)
+  }
+
+  /// Length of the longest common suffix of `xs[..i]` and `xs[..j]`, for i, j in 0..=n.
+  /// So `xs[i-k..i] == xs[j-k..j]` for k up to the result.
+  pub fn lce_backward(&self, i: usize, j: usize) -> usize {
+    let n = self.len();
+    assert!(i <= n && j <= n, "Query index out of range 0..={n}: ({i}, {j}).");
+    self.backward.query(n - i, n - j)
+  }
+
+  /// Length of the indexed sequence.
+  pub fn len(&self) -> usize {
+    self.forward.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.forward.is_empty()
+  }
+
+  /// Structure over the sequence, answering `lce_forward`.
+  pub fn forward(&self) -> &SARMQ<I> {
+    &self.forward
+  }
+
+  /// Structure over the reversed sequence, where position i stands for `xs[..n-i]` reversed.
+  pub fn backward(&self) -> &SARMQ<I> {
+    &self.backward
+  }
+
+}
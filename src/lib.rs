@@ -1,46 +1,247 @@
+#![allow(clippy::upper_case_acronyms)]
 
-mod rmq;
-mod sa;
+pub mod alphabet;
+pub mod approx;
+mod bits;
+pub mod bwt;
+pub mod compressed;
+mod error;
+pub mod fm_index;
+pub mod generalized;
+mod index;
+pub mod interval_tree;
+pub mod lca;
+pub mod lce;
+mod persist;
+pub mod repeats;
+pub mod rmq;
+pub mod sa;
+mod search;
+mod substrings;
+mod succinct;
+#[cfg(test)]
 mod tests;
 
+use std::hash::Hash;
+
+pub use error::{PersistError, SarmqError};
+pub use index::IndexType;
+
+use rmq::{EulerRMQ, RangeMinQuery, RangeMinView};
+
+use index::check_len;
+
 /// Creates a SA+RMQ structure which allows to query for any two indices in the sequence,
 /// what is the length of the longest common prefix of the suffixes starting at these indices.
 /// O(n) creation time, O(1) query time
-pub fn create_sarmq(a: &Vec<usize>) -> SARMQ {
+/// Assumes integers in 0..n range, of any of the index types, e.g. `&[u32]`,
+/// see `create_sarmq_from_symbols` for other alphabets.
+pub fn create_sarmq(a: &[impl IndexType]) -> SARMQ {
+  build_sarmq(a)
+}
+
+fn build_sarmq<I: IndexType, R: RangeMinQuery<I>>(a: &[impl IndexType]) -> SARMQ<I, R> {
   // sort suffixes
-  let sa = sa::SA::create_suffix_array(a);
+  let sa = sa::SA::<I>::create_suffix_array(a);
   // lcp of neighbouring suffixes in the sorted order
   let lcp = sa::lcp(a, &sa);
-  // tree of indices in the sorted order, arranged by lcp values
-  let cart_tree = sa::cartesian_tree(&lcp);
-  // array of the above indices, depths in the tree recorded
-  let euler_walk = cart_tree.euler_walk();
-  // RMQ on the euler traversal order with depths as keys
-  let rmq_data = euler_walk.euler.iter().map(|&x| euler_walk.depth[x]).collect();
-  let rmq = rmq::RMQ::create_rmq(&rmq_data);
-  // for getting lcp values back
-  let lcp_euler: Vec<usize> = euler_walk.euler.iter().map(|i: &usize| lcp[*i]).collect();
-  SARMQ { indice_into_rmq : euler_walk.first_occ, rmq, lcp_euler: lcp_euler, sa_inverse: sa.sa_inverse }
-}
-
-/// Implements O(1) time queries for the longest common prefix of the suffixes starting at the given indices. 
-pub struct SARMQ {
-  indice_into_rmq: Vec<usize>,
-  rmq: rmq::RMQ,
-  lcp_euler : Vec<usize>,
-  sa_inverse: Vec<usize>,
-}
-
-impl SARMQ {
+  // minima of ranges of lcp, the default one through the euler walk of the cartesian tree with RMQ on its depths
+  let rmq = R::build(&lcp);
+  SARMQ { rmq, sa: sa.sa, sa_inverse: sa.sa_inverse, lcp }
+}
+
+/// Like `create_sarmq`, but checks that the integers are in 0..n range and reports an error instead of panicking.
+/// To remap an arbitrary alphabet instead, use `create_sarmq_from_symbols`.
+pub fn try_create_sarmq(a: &[impl IndexType]) -> Result<SARMQ, SarmqError> {
+  sa::check_alphabet(a)?;
+  Ok(create_sarmq(a))
+}
+
+/// Like `try_create_sarmq`, but stores indices as I, e.g. `try_create_sarmq_with_index::<u32>` uses half of the memory.
+/// The Euler tour over the sequence takes 2n indices, so I has to fit 2n, e.g. u32 supports sequences up to 2^31.
+/// Other alphabets can be mapped to 0..n beforehand with `alphabet::compact`.
+pub fn try_create_sarmq_with_index<I: IndexType>(a: &[impl IndexType]) -> Result<SARMQ<I>, SarmqError> {
+  check_len(a.len(), I::MAX / 2)?;
+  sa::check_alphabet(a)?;
+  Ok(build_sarmq(a))
+}
+
+/// Like `create_sarmq`, with another range minimum query backend, e.g. `create_sarmq_with_rmq::<rmq::SuccinctRMQ>`
+/// for an RMQ in bits instead of words, or `rmq::SparseTableRMQ` for the fastest queries, see `rmq::RangeMinQuery`.
+pub fn create_sarmq_with_rmq<R: RangeMinQuery>(a: &[impl IndexType]) -> SARMQ<usize, R> {
+  build_sarmq(a)
+}
+
+/// Like `try_create_sarmq_with_index`, with another range minimum query backend, see `create_sarmq_with_rmq`.
+pub fn try_create_sarmq_with_rmq<I: IndexType, R: RangeMinQuery<I>>(a: &[impl IndexType]) -> Result<SARMQ<I, R>, SarmqError> {
+  check_len(a.len(), I::MAX / 2)?;
+  sa::check_alphabet(a)?;
+  Ok(build_sarmq(a))
+}
+
+/// Like `create_sarmq`, for integers coming from an iterator, collected as they come, e.g. `u32`s decoded from a file.
+pub fn create_sarmq_from_iter<T: IndexType>(a: impl IntoIterator<Item = T>) -> SARMQ {
+  create_sarmq(&a.into_iter().collect::<Vec<T>>())
+}
+
+/// Creates SA+RMQ over a byte string, queries index bytes.
+pub fn create_sarmq_from_bytes(a: &[u8]) -> SARMQ {
+  create_sarmq(&alphabet::compact_bytes(a))
+}
+
+/// Creates SA+RMQ over the bytes of a utf-8 string, queries index bytes (as in `str` slicing).
+pub fn create_sarmq_from_str(a: &str) -> SARMQ {
+  create_sarmq_from_bytes(a.as_bytes())
+}
+
+/// Creates SA+RMQ over the chars of a string, queries index chars (as in `a.chars().nth(i)`).
+pub fn create_sarmq_from_chars(a: &str) -> SARMQ {
+  create_sarmq_from_symbols(&a.chars().collect::<Vec<char>>())
+}
+
+/// Creates SA+RMQ over a sequence of any ordered symbols, e.g. `&[char]` or `&[String]`.
+/// Symbols are compacted to their ranks first, so they don't need to be in 0..n range.
+pub fn create_sarmq_from_symbols<T: Ord + Hash>(a: &[T]) -> SARMQ {
+  create_sarmq(&alphabet::compact(a))
+}
+
+/// Implements O(1) time queries for the longest common prefix of the suffixes starting at the given indices.
+/// Indices are stored as I, see `try_create_sarmq_with_index`, and the minima over lcp come from R,
+/// see `create_sarmq_with_rmq`. Saving needs the default R.
+pub struct SARMQ<I = usize, R = EulerRMQ<I>> {
+  rmq: R,
+  sa: Vec<I>,
+  sa_inverse: Vec<I>,
+  lcp: Vec<I>,
+}
+
+/// `SARMQ` borrowing its arrays, e.g. from a memory mapped file, see `SARMQView::from_bytes`.
+/// R is the borrowed range minimum query backend, see `rmq::RangeMinQuery::View`.
+#[derive(Clone, Copy)]
+pub struct SARMQView<'a, I = usize, R = rmq::EulerRMQView<'a, I>> {
+  rmq: R,
+  sa: &'a [I],
+  sa_inverse: &'a [I],
+  lcp: &'a [I],
+}
+
+// lcp of the suffixes i and j from their ranks and the leftmost minimum of lcp over a range of ranks
+fn lcp_query(n: usize, i: usize, j: usize,
+    rank: impl Fn(usize) -> usize, lcp: impl Fn(usize) -> usize, argmin: impl Fn(usize, usize) -> usize) -> usize {
+  assert!(i <= n && j <= n, "Query index out of range 0..={n}: ({i}, {j}).");
+  if i == j { return n - i }
+  if i == n || j == n { return 0 }
+  let (ii, jj) = (rank(i), rank(j));
+  lcp(argmin(ii.min(jj), ii.max(jj) - 1))
+}
+
+// the first of the query indices out of 0..=len range
+fn check_query(len: usize, i: usize, j: usize) -> Result<(), SarmqError> {
+  match [i, j].into_iter().find(|&index| index > len) {
+    Some(index) => Err(SarmqError::IndexOutOfRange { index, len }),
+    None => Ok(()),
+  }
+}
+
+impl<'a, I: IndexType, R: RangeMinView<I>> SARMQView<'a, I, R> {
+  /// Returns the length of the longest common prefix of the suffixes starting at the given indices.
+  /// Indices are in 0..=n range, where n stands for the empty suffix, so its lcp with anything is 0.
+  /// Panics for indices larger than n, see `checked_query` and `try_query`.
+  pub fn query(&self, i: usize, j: usize) -> usize {
+    lcp_query(self.len(), i, j,
+      |i| self.sa_inverse[i].to_usize(), |rank| self.lcp[rank].to_usize(), |l, r| self.rmq.query(self.lcp, l, r))
+  }
+
+  /// Like `query`, but returns None for indices out of 0..=n range.
+  pub fn checked_query(&self, i: usize, j: usize) -> Option<usize> {
+    self.try_query(i, j).ok()
+  }
+
+  /// Like `query`, but reports indices out of 0..=n range as an error.
+  pub fn try_query(&self, i: usize, j: usize) -> Result<usize, SarmqError> {
+    check_query(self.len(), i, j).map(|()| self.query(i, j))
+  }
+
+  /// Length of the indexed sequence.
+  pub fn len(&self) -> usize {
+    self.sa.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.sa.is_empty()
+  }
+
+  /// Suffix array: starting indices of the suffixes in lexicographical order.
+  pub fn sa(&self) -> &'a [I] {
+    self.sa
+  }
+
+  /// Inverse of the suffix array: rank of the suffix starting at each index.
+  pub fn sa_inverse(&self) -> &'a [I] {
+    self.sa_inverse
+  }
+
+  /// lcp[i] is the length of the longest common prefix of the suffixes `sa[i]` and `sa[i+1]`, `lcp[n-1] = 0`.
+  pub fn lcp(&self) -> &'a [I] {
+    self.lcp
+  }
+}
+
+impl<I: IndexType, R: RangeMinQuery<I>> SARMQ<I, R> {
   /// Returns the length of the longest common prefix of the suffixes starting at the given indices.
+  /// Indices are in 0..=n range, where n stands for the empty suffix, so its lcp with anything is 0.
+  /// Panics for indices larger than n, see `checked_query` and `try_query`.
   pub fn query(&self, i: usize, j: usize) -> usize {
-    if i == j { return self.indice_into_rmq.len() - i }
-    let ii = self.sa_inverse[i];
-    let jj = self.sa_inverse[j];
-    let (iii, jjj) =
-      (self.indice_into_rmq[ii.min(jj)], self.indice_into_rmq[ii.max(jj)-1]);
-    self.lcp_euler[
-      self.rmq.query(iii.min(jjj), iii.max(jjj))
-    ]
-  }
-}
\ No newline at end of file
+    lcp_query(self.len(), i, j,
+      |i| self.sa_inverse[i].to_usize(), |rank| self.lcp[rank].to_usize(), |l, r| self.rmq.query(&self.lcp, l, r))
+  }
+
+  /// Like `query`, but returns None for indices out of 0..=n range.
+  pub fn checked_query(&self, i: usize, j: usize) -> Option<usize> {
+    self.try_query(i, j).ok()
+  }
+
+  /// Like `query`, but reports indices out of 0..=n range as an error.
+  pub fn try_query(&self, i: usize, j: usize) -> Result<usize, SarmqError> {
+    check_query(self.len(), i, j).map(|()| self.query(i, j))
+  }
+
+  /// Borrows the arrays, the view answers the same queries.
+  pub fn view(&self) -> SARMQView<'_, I, R::View<'_>> {
+    SARMQView {
+      rmq: self.rmq.view(),
+      sa: &self.sa,
+      sa_inverse: &self.sa_inverse,
+      lcp: &self.lcp,
+    }
+  }
+
+  /// Length of the indexed sequence.
+  pub fn len(&self) -> usize {
+    self.sa.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.sa.is_empty()
+  }
+
+  /// Suffix array: starting indices of the suffixes in lexicographical order.
+  pub fn sa(&self) -> &[I] {
+    &self.sa
+  }
+
+  /// Inverse of the suffix array: rank of the suffix starting at each index.
+  pub fn sa_inverse(&self) -> &[I] {
+    &self.sa_inverse
+  }
+
+  /// lcp[i] is the length of the longest common prefix of the suffixes `sa[i]` and `sa[i+1]`, `lcp[n-1] = 0`.
+  pub fn lcp(&self) -> &[I] {
+    &self.lcp
+  }
+
+  /// Range minimum queries over lcp.
+  pub fn rmq(&self) -> &R {
+    &self.rmq
+  }
+}
@@ -0,0 +1,363 @@
+// Versioned binary format for saving built structures, so that they don't have to be rebuilt.
+// Layout: magic bytes, format version, width of the stored indices, then the contents
+// (arrays as a u64 length followed by little-endian values), and a checksum of all of the above.
+// Every array starts at a multiple of 8 bytes, so that a file mapped into memory can be queried in place.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::mem::size_of;
+use std::path::Path;
+
+use crate::index::IndexType;
+use crate::{rmq, PersistError, SARMQView, SARMQ};
+
+const FORMAT_VERSION: u32 = 3;
+const SARMQ_MAGIC: &[u8; 8] = b"SARMQ\0\0\0";
+// values converted per chunk of an array
+const CHUNK: usize = 1 << 12;
+const ALIGN: usize = 8;
+
+// FNV-1a
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+fn fnv(hash: u64, bytes: &[u8]) -> u64 {
+  bytes.iter().fold(hash, |h, &b| (h ^ b as u64).wrapping_mul(FNV_PRIME))
+}
+
+/// Zeros after an array of the given size in bytes, up to the alignment.
+fn padding(bytes: usize) -> usize {
+  bytes.next_multiple_of(ALIGN) - bytes
+}
+
+/// Writes values in the format, keeping the checksum of everything written.
+pub(crate) struct Writer<W> {
+  inner: W,
+  hash: u64,
+}
+
+impl<W: Write> Writer<W> {
+  pub fn new(inner: W) -> Self {
+    Writer { inner, hash: FNV_OFFSET }
+  }
+
+  pub fn header<I: IndexType>(&mut self, magic: &[u8; 8]) -> io::Result<()> {
+    self.bytes(magic)?;
+    self.u32(FORMAT_VERSION)?;
+    self.u32(size_of::<I>() as u32)
+  }
+
+  pub fn bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+    self.hash = fnv(self.hash, bytes);
+    self.inner.write_all(bytes)
+  }
+
+  pub fn u32(&mut self, x: u32) -> io::Result<()> {
+    self.bytes(&x.to_le_bytes())
+  }
+
+  pub fn u64(&mut self, x: u64) -> io::Result<()> {
+    self.bytes(&x.to_le_bytes())
+  }
+
+  pub fn array<I: IndexType>(&mut self, xs: &[I]) -> io::Result<()> {
+    self.u64(xs.len() as u64)?;
+    let width = size_of::<I>();
+    let mut buf = Vec::with_capacity(CHUNK * width);
+    for chunk in xs.chunks(CHUNK) {
+      buf.clear();
+      chunk.iter().for_each(|x| buf.extend_from_slice(&(x.to_usize() as u64).to_le_bytes()[..width]));
+      self.bytes(&buf)?;
+    }
+    self.bytes(&[0; ALIGN][..padding(std::mem::size_of_val(xs))])
+  }
+
+  pub fn byte_array(&mut self, xs: &[u8]) -> io::Result<()> {
+    self.u64(xs.len() as u64)?;
+    self.bytes(xs)?;
+    self.bytes(&[0; ALIGN][..padding(xs.len())])
+  }
+
+  /// Appends the checksum.
+  pub fn finish(mut self) -> io::Result<()> {
+    let hash = self.hash;
+    self.inner.write_all(&hash.to_le_bytes())?;
+    self.inner.flush()
+  }
+}
+
+/// Reads values in the format, keeping the checksum of everything read.
+pub(crate) struct Reader<R> {
+  inner: R,
+  hash: u64,
+}
+
+impl<R: Read> Reader<R> {
+  pub fn new(inner: R) -> Self {
+    Reader { inner, hash: FNV_OFFSET }
+  }
+
+  pub fn header<I: IndexType>(&mut self, magic: &[u8; 8]) -> Result<(), PersistError> {
+    let mut found = [0; 16];
+    self.bytes(&mut found)?;
+    check_header::<I>(&found, magic)
+  }
+
+  pub fn bytes(&mut self, buf: &mut [u8]) -> Result<(), PersistError> {
+    self.inner.read_exact(buf)?;
+    self.hash = fnv(self.hash, buf);
+    Ok(())
+  }
+
+  pub fn u64(&mut self) -> Result<u64, PersistError> {
+    let mut buf = [0; 8];
+    self.bytes(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+  }
+
+  pub fn usize(&mut self) -> Result<usize, PersistError> {
+    usize::try_from(self.u64()?).map_err(|_| PersistError::Corrupted("length larger than usize"))
+  }
+
+  /// Reads the length of an array first, which has to be the expected one.
+  fn len(&mut self, expected: usize) -> Result<usize, PersistError> {
+    let len = self.usize()?;
+    if len != expected {
+      return Err(PersistError::Corrupted("array of a wrong length"));
+    }
+    Ok(len)
+  }
+
+  pub fn array<I: IndexType>(&mut self, expected: usize) -> Result<Vec<I>, PersistError> {
+    let len = self.len(expected)?;
+    let width = size_of::<I>();
+    // grows along with the data actually read, so a corrupted length doesn't allocate it all upfront
+    let mut xs = Vec::with_capacity(len.min(CHUNK));
+    let mut buf = vec![0; CHUNK * width];
+    let mut left = len;
+    while left > 0 {
+      let k = left.min(CHUNK);
+      self.bytes(&mut buf[..k * width])?;
+      xs.extend(buf[..k * width].chunks_exact(width).map(|b| {
+        let mut le = [0; 8];
+        le[..width].copy_from_slice(b);
+        I::from_usize(u64::from_le_bytes(le) as usize)
+      }));
+      left -= k;
+    }
+    self.bytes(&mut [0; ALIGN][..padding(len * width)])?;
+    Ok(xs)
+  }
+
+  pub fn byte_array(&mut self, expected: usize) -> Result<Vec<u8>, PersistError> {
+    let len = self.len(expected)?;
+    let mut xs = vec![0; len];
+    self.bytes(&mut xs)?;
+    self.bytes(&mut [0; ALIGN][..padding(len)])?;
+    Ok(xs)
+  }
+
+  /// Checks the checksum.
+  pub fn finish(mut self) -> Result<(), PersistError> {
+    let mut stored = [0; 8];
+    self.inner.read_exact(&mut stored)?;
+    if u64::from_le_bytes(stored) != self.hash {
+      return Err(PersistError::ChecksumMismatch);
+    }
+    Ok(())
+  }
+}
+
+/// Reads values of the format in place, from a buffer holding the whole saved structure.
+pub(crate) struct ByteReader<'a> {
+  buf: &'a [u8],
+  pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+  /// Checks the header, and if `verify` also the checksum of the whole buffer.
+  pub fn new<I: IndexType>(buf: &'a [u8], magic: &[u8; 8], verify: bool) -> Result<Self, PersistError> {
+    if cfg!(target_endian = "big") {
+      return Err(PersistError::Unsupported("the format is little-endian"));
+    }
+    if ! (buf.as_ptr() as usize).is_multiple_of(ALIGN) {
+      return Err(PersistError::Unsupported("the buffer is not aligned to 8 bytes"));
+    }
+    let mut r = ByteReader { buf, pos: 0 };
+    check_header::<I>(r.take(16)?, magic)?;
+    if verify {
+      let contents = buf.len().checked_sub(8).ok_or(PersistError::Corrupted("truncated"))?;
+      let stored = u64::from_le_bytes(buf[contents..].try_into().unwrap());
+      if fnv(FNV_OFFSET, &buf[..contents]) != stored {
+        return Err(PersistError::ChecksumMismatch);
+      }
+    }
+    Ok(r)
+  }
+
+  fn take(&mut self, len: usize) -> Result<&'a [u8], PersistError> {
+    let bytes = self.pos.checked_add(len).and_then(|end| self.buf.get(self.pos..end))
+      .ok_or(PersistError::Corrupted("truncated"))?;
+    self.pos += len;
+    Ok(bytes)
+  }
+
+  pub fn usize(&mut self) -> Result<usize, PersistError> {
+    let x = u64::from_le_bytes(self.take(8)?.try_into().unwrap());
+    usize::try_from(x).map_err(|_| PersistError::Corrupted("length larger than usize"))
+  }
+
+  pub fn array<I: IndexType>(&mut self) -> Result<&'a [I], PersistError> {
+    let len = self.usize()?;
+    let bytes = len.checked_mul(size_of::<I>()).ok_or(PersistError::Corrupted("length larger than usize"))?;
+    let data = self.take(bytes)?;
+    self.take(padding(bytes))?;
+    // SAFETY: IndexType is only implemented for primitive unsigned integers, for which any bytes are a valid value.
+    let (prefix, xs, suffix) = unsafe { data.align_to::<I>() };
+    if ! prefix.is_empty() || ! suffix.is_empty() {
+      return Err(PersistError::Corrupted("misaligned array"));
+    }
+    Ok(xs)
+  }
+
+  pub fn byte_array(&mut self) -> Result<&'a [u8], PersistError> {
+    let len = self.usize()?;
+    let data = self.take(len)?;
+    self.take(padding(len))?;
+    Ok(data)
+  }
+
+  /// Checks that only the checksum is left.
+  pub fn finish(self) -> Result<(), PersistError> {
+    if self.pos + 8 != self.buf.len() {
+      return Err(PersistError::Corrupted("unexpected length"));
+    }
+    Ok(())
+  }
+}
+
+fn check_header<I: IndexType>(header: &[u8], magic: &[u8; 8]) -> Result<(), PersistError> {
+  if &header[..8] != magic {
+    return Err(PersistError::BadMagic);
+  }
+  let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+  if version != FORMAT_VERSION {
+    return Err(PersistError::UnsupportedVersion { version });
+  }
+  let found = u32::from_le_bytes(header[12..16].try_into().unwrap()) as usize;
+  if found != size_of::<I>() {
+    return Err(PersistError::IndexWidthMismatch { expected: size_of::<I>(), found });
+  }
+  Ok(())
+}
+
+/// Fails with `Corrupted(what)` unless all values are below bound.
+pub(crate) fn check_below<I: IndexType>(xs: &[I], bound: usize, what: &'static str) -> Result<(), PersistError> {
+  if xs.iter().all(|x| x.to_usize() < bound) {
+    Ok(())
+  } else {
+    Err(PersistError::Corrupted(what))
+  }
+}
+
+impl<'a, I: IndexType> SARMQView<'a, I> {
+
+  /// Writes the structure in a versioned binary format, see `SARMQ::load` and `SARMQView::from_bytes`.
+  pub fn save(&self, w: impl Write) -> io::Result<()> {
+    let mut w = Writer::new(w);
+    w.header::<I>(SARMQ_MAGIC)?;
+    w.u64(self.len() as u64)?;
+    w.array(self.sa)?;
+    w.array(self.sa_inverse)?;
+    w.array(self.lcp)?;
+    self.rmq.write_to(&mut w)?;
+    w.finish()
+  }
+
+  /// Queries a structure written by `SARMQ::save` in place, without copying it, e.g. from a memory mapped file.
+  /// The buffer has to be aligned to 8 bytes, as memory maps are, e.g. `&memmap2::Mmap` of a saved file,
+  /// which processes mapping the same file share.
+  /// Checks the checksum and that all indices are in range, which takes a pass over the buffer,
+  /// see `from_bytes_trusted` for skipping that.
+  pub fn from_bytes(buf: &'a [u8]) -> Result<Self, PersistError> {
+    let view = Self::read(ByteReader::new::<I>(buf, SARMQ_MAGIC, true)?)?;
+    view.check(true)?;
+    Ok(view)
+  }
+
+  /// Like `from_bytes`, but only checks the header and the lengths of the arrays, in O(1) time.
+  /// Queries on a corrupted buffer can then give wrong answers or panic.
+  pub fn from_bytes_trusted(buf: &'a [u8]) -> Result<Self, PersistError> {
+    let view = Self::read(ByteReader::new::<I>(buf, SARMQ_MAGIC, false)?)?;
+    view.check(false)?;
+    Ok(view)
+  }
+
+  fn read(mut r: ByteReader<'a>) -> Result<Self, PersistError> {
+    let n = r.usize()?;
+    let view = SARMQView {
+      sa: r.array()?,
+      sa_inverse: r.array()?,
+      lcp: r.array()?,
+      rmq: rmq::EulerRMQView::read_from(&mut r)?,
+    };
+    r.finish()?;
+    if view.len() != n {
+      return Err(PersistError::Corrupted("array of a wrong length"));
+    }
+    Ok(view)
+  }
+
+  /// Checks that the arrays have consistent lengths, and if `values` also that the indices in them are in range.
+  fn check(&self, values: bool) -> Result<(), PersistError> {
+    let n = self.sa.len();
+    if [self.sa_inverse, self.lcp].iter().any(|xs| xs.len() != n) {
+      return Err(PersistError::Corrupted("array of a wrong length"));
+    }
+    self.rmq.check(n, values)?;
+    if values {
+      check_below(self.sa, n, "suffix array out of range")?;
+      check_below(self.sa_inverse, n, "inverse suffix array out of range")?;
+      check_below(self.lcp, n + 1, "lcp out of range")?;
+    }
+    Ok(())
+  }
+
+}
+
+impl<I: IndexType> SARMQ<I> {
+
+  /// Writes the structure in a versioned binary format, see `load` and `SARMQView::from_bytes`.
+  pub fn save(&self, w: impl Write) -> io::Result<()> {
+    self.view().save(w)
+  }
+
+  /// Reads a structure written by `save` with the same index type.
+  /// Reports files of other kinds, versions or index types, corrupted and truncated files as errors.
+  pub fn load(r: impl Read) -> Result<Self, PersistError> {
+    let mut r = Reader::new(r);
+    r.header::<I>(SARMQ_MAGIC)?;
+    // lengths are known upfront, so a corrupted one is reported before reading that much
+    let n = r.usize()?;
+    let sarmq = SARMQ {
+      sa: r.array(n)?,
+      sa_inverse: r.array(n)?,
+      lcp: r.array(n)?,
+      rmq: rmq::EulerRMQ::read_from(&mut r, n)?,
+    };
+    r.finish()?;
+    sarmq.view().check(true)?;
+    Ok(sarmq)
+  }
+
+  /// `save` to a new file at path.
+  pub fn save_to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+    self.save(BufWriter::new(File::create(path)?))
+  }
+
+  /// `load` from the file at path.
+  pub fn load_from_file(path: impl AsRef<Path>) -> Result<Self, PersistError> {
+    Self::load(BufReader::new(File::open(path)?))
+  }
+
+}
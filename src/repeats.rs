@@ -0,0 +1,212 @@
+// Repeats of a sequence read off its suffix array and lcp array, without building a suffix tree.
+// lcp-intervals (Abouelhoda, Kurtz, Ohlebusch) are ranges of suffixes sharing a prefix, which are
+// the internal nodes of the suffix tree; they are enumerated bottom up with a stack, one at a time.
+
+use std::ops::Range;
+
+use crate::index::IndexType;
+use crate::rmq::{RangeMinQuery, RangeMinView};
+use crate::{SARMQView, SARMQ};
+
+/// Range of suffixes, whose longest common prefix has length `lcp`,
+/// and which is maximal, so that the suffixes just outside of it share a shorter prefix with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LcpInterval {
+  pub lcp: usize,
+  /// Ranks of the suffixes, at least two of them.
+  pub sa_range: Range<usize>,
+  // whether any smaller lcp-interval lies inside, that is it isn't a leaf of the lcp-interval tree
+  pub(crate) has_child: bool,
+}
+
+/// Substring occurring at least twice in the sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repeat<'a, I = usize> {
+  pub len: usize,
+  /// Ranks of the suffixes starting with the substring.
+  pub sa_range: Range<usize>,
+  /// Starting indices of the occurrences, in the suffix array order.
+  pub occurrences: &'a [I],
+}
+
+/// Whether the symbols right before the suffixes of an interval are all the same one, as far as seen.
+#[derive(Clone, Copy)]
+enum Left {
+  None,
+  // all the same as before the suffix of this rank
+  Same(usize),
+  Diverse,
+}
+
+/// Bottom up enumeration of the lcp-intervals, each one after all intervals inside it.
+/// same_left(r, s) tells whether the suffixes of ranks r and s are preceded by the same symbol.
+struct Intervals<'a, I, F> {
+  lcp: &'a [I],
+  same_left: F,
+  // boundary between ranks i-1 and i to process next
+  i: usize,
+  // whether the suffix of rank i-1 went into its interval already
+  leaf_done: bool,
+  // open intervals: lcp, first rank, whether a closed interval lies inside, left symbols
+  stack: Vec<(usize, usize, bool, Left)>,
+  // first rank, whether a closed interval lies inside and left symbols of the interval to open at i
+  lb: usize,
+  has_child: bool,
+  left: Left,
+}
+
+impl<'a, I: IndexType, F: Fn(usize, usize) -> bool> Intervals<'a, I, F> {
+  fn new(lcp: &'a [I], same_left: F) -> Self {
+    Intervals {
+      lcp, same_left, i: 1, leaf_done: false,
+      stack: vec![(0, 0, false, Left::None)],
+      lb: 0, has_child: false, left: Left::None,
+    }
+  }
+
+  fn merge(&self, a: Left, b: Left) -> Left {
+    match (a, b) {
+      (Left::None, x) | (x, Left::None) => x,
+      (Left::Same(r), Left::Same(s)) if (self.same_left)(r, s) => Left::Same(r),
+      _ => Left::Diverse,
+    }
+  }
+
+  /// Closes the intervals ending at rank i-1 one by one, then opens the one going on from there.
+  fn next_interval(&mut self) -> Option<(LcpInterval, Left)> {
+    let n = self.lcp.len();
+    while self.i <= n {
+      let i = self.i;
+      // the root, with lcp 0, is never closed
+      let l = if i < n { self.lcp[i - 1].to_usize() } else { 0 };
+      let top = self.stack.len() - 1;
+      if ! self.leaf_done {
+        // rank i-1 goes into the deepest interval around it, which is either open or opens at i
+        let leaf = Left::Same(i - 1);
+        if l <= self.stack[top].0 {
+          self.stack[top].3 = self.merge(self.stack[top].3, leaf);
+        } else {
+          self.left = leaf;
+        }
+        self.leaf_done = true;
+      }
+      if l < self.stack[top].0 {
+        let (lcp, lb, has_child, left) = self.stack.pop().unwrap();
+        // the closed interval goes into the next open one, or into the one opening at i
+        let parent = top - 1;
+        if l <= self.stack[parent].0 {
+          self.stack[parent].2 = true;
+          self.stack[parent].3 = self.merge(self.stack[parent].3, left);
+        } else {
+          self.has_child = true;
+          self.left = self.merge(self.left, left);
+        }
+        self.lb = lb;
+        return Some((LcpInterval { lcp, sa_range: lb..i, has_child }, left));
+      }
+      if l > self.stack[top].0 {
+        self.stack.push((l, self.lb, self.has_child, self.left));
+      }
+      self.i += 1;
+      self.leaf_done = false;
+      self.lb = i;
+      self.has_child = false;
+      self.left = Left::None;
+    }
+    None
+  }
+}
+
+impl<'a, I: IndexType, F: Fn(usize, usize) -> bool> Iterator for Intervals<'a, I, F> {
+  type Item = (LcpInterval, Left);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.next_interval()
+  }
+}
+
+impl<'a, I: IndexType, R: RangeMinView<I> + 'a> SARMQView<'a, I, R> {
+
+  /// All lcp-intervals, except of the whole suffix array, each after the ones inside it.
+  /// They stand for the distinct substrings occurring at least twice and followed by different symbols.
+  /// O(n) time in total, keeping a stack of the intervals around the current suffix.
+  pub fn lcp_intervals(&self) -> impl Iterator<Item = LcpInterval> + 'a {
+    Intervals::new(self.lcp, |_, _| true).map(|(interval, _)| interval)
+  }
+
+  /// Longest substrings occurring at least twice, all of them if there are several of the same length.
+  /// O(n) time in total.
+  pub fn longest_repeats(&self) -> impl Iterator<Item = Repeat<'a, I>> + 'a {
+    let len = self.lcp.iter().map(|x| x.to_usize()).max().unwrap_or(0);
+    let view = *self;
+    self.lcp_intervals().filter(move |interval| interval.lcp == len).map(move |interval| view.repeat(interval))
+  }
+
+  /// Maximal repeats: substrings occurring at least twice, which can't be extended
+  /// to the left or to the right without losing an occurrence.
+  /// text is the indexed sequence, see `SARMQView::sa_range`.
+  /// O(n) time in total.
+  pub fn maximal_repeats<T: Eq>(&self, text: &'a [T]) -> impl Iterator<Item = Repeat<'a, I>> + 'a {
+    assert_eq!(text.len(), self.len(), "Text of a different length than the indexed one.");
+    let view = *self;
+    Intervals::new(self.lcp, move |r, s| view.same_left(text, r, s))
+      .filter(|(_, left)| matches!(left, Left::Diverse))
+      .map(move |(interval, _)| view.repeat(interval))
+  }
+
+  /// Supermaximal repeats: maximal repeats which don't occur inside of another maximal repeat.
+  /// These are the lcp-intervals with no intervals inside, whose suffixes are preceded by pairwise different symbols.
+  /// O(n log n) time in total.
+  pub fn supermaximal_repeats<T: Ord>(&self, text: &'a [T]) -> impl Iterator<Item = Repeat<'a, I>> + 'a {
+    assert_eq!(text.len(), self.len(), "Text of a different length than the indexed one.");
+    let view = *self;
+    self.lcp_intervals()
+      .filter(|interval| ! interval.has_child)
+      .filter(move |interval| {
+        // the first suffix is preceded by None, different from the others
+        let mut left: Vec<Option<&T>> = view.sa[interval.sa_range.clone()].iter()
+          .map(|x| x.to_usize().checked_sub(1).map(|i| &text[i]))
+          .collect();
+        left.sort_unstable();
+        left.windows(2).all(|w| w[0] != w[1])
+      })
+      .map(move |interval| view.repeat(interval))
+  }
+
+  fn repeat(&self, interval: LcpInterval) -> Repeat<'a, I> {
+    Repeat { len: interval.lcp, occurrences: &self.sa[interval.sa_range.clone()], sa_range: interval.sa_range }
+  }
+
+  // the first suffix isn't preceded by anything, so differs from all others
+  fn same_left<T: Eq>(&self, text: &[T], r: usize, s: usize) -> bool {
+    match (self.sa[r].to_usize(), self.sa[s].to_usize()) {
+      (0, _) | (_, 0) => false,
+      (i, j) => text[i - 1] == text[j - 1],
+    }
+  }
+
+}
+
+impl<I: IndexType, R: RangeMinQuery<I>> SARMQ<I, R> {
+
+  /// All lcp-intervals, see `SARMQView::lcp_intervals`.
+  pub fn lcp_intervals(&self) -> impl Iterator<Item = LcpInterval> + '_ {
+    self.view().lcp_intervals()
+  }
+
+  /// Longest substrings occurring at least twice, see `SARMQView::longest_repeats`.
+  pub fn longest_repeats(&self) -> impl Iterator<Item = Repeat<'_, I>> + '_ {
+    self.view().longest_repeats()
+  }
+
+  /// Maximal repeats, see `SARMQView::maximal_repeats`.
+  pub fn maximal_repeats<'a, T: Eq>(&'a self, text: &'a [T]) -> impl Iterator<Item = Repeat<'a, I>> + 'a {
+    self.view().maximal_repeats(text)
+  }
+
+  /// Supermaximal repeats, see `SARMQView::supermaximal_repeats`.
+  pub fn supermaximal_repeats<'a, T: Ord>(&'a self, text: &'a [T]) -> impl Iterator<Item = Repeat<'a, I>> + 'a {
+    self.view().supermaximal_repeats(text)
+  }
+
+}
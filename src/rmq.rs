@@ -1,9 +1,17 @@
-// Implements range minimum query (RMQ) structure, assuming in the sequence numbers differ at most by 1.
+// Implements range minimum query (RMQ) structure, assuming in the sequence numbers differ at most by 1,
+// and on top of it range queries for any values and selectors (min, max, ...), through the cartesian tree.
+// `SuccinctRMQ` answers the same queries for any values in bits rather than words.
+// `RangeMinQuery` is the interface of the structures which can back `SARMQ`.
 
+use std::cmp::Ordering;
+use std::io::{self, Read, Write};
 use std::{iter, vec};
 
-use num::Bounded;
-use unzip3::Unzip3;
+use crate::index::IndexType;
+use crate::persist::{check_below, ByteReader, Reader, Writer};
+use crate::{sa, PersistError};
+
+pub use crate::succinct::SuccinctRMQ;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Min<A, B> {
@@ -24,32 +32,59 @@ impl<A : Ord, B> Min<A, B> {
   }
 }
 
-impl<A : Bounded, B : Default> Default for Min<A, B> {
+impl<A : IndexType, B : Default> Default for Min<A, B> {
   fn default() -> Self {
-    Min { min: A::max_value(), argmin: B::default() }
+    Min { min: A::from_usize(A::MAX), argmin: B::default() }
   }
 }
 
-/// Structure for range minimum queries (argmin) on a sequence of numbers.
+/// Structure for range minimum queries (argmin) on a sequence of numbers, where neighbours differ by -1/0/+1.
 /// O(n) space, O(1) query time.
+/// Values and indices are stored as I, in flat arrays, see `RMQView` for querying them in place.
 // ranges inclusive for both ends
-pub struct RMQ {
+pub struct RMQ<I = usize> {
   // n : usize,
   b : usize, // ~ log n
   // m : usize, // n / b
   // logm : usize,
-  ranges_answers: Vec<Vec<Min<usize, usize>>>, // answers for 2^k sized ranges
-  block_firsts: Vec<usize>,    // values for first elements of each block
-  block_types: Vec<usize>, // block types
-  block_answers: Vec<Vec<Min<i64, usize>>>, // precomputed answers for each block type
+  ranges_answers: Vec<I>, // answers for 2^k sized ranges, (min, argmin) pairs for each k and block
+  block_firsts: Vec<I>,    // values for first elements of each block
+  block_types: Vec<I>, // block types
+  block_answers: Vec<u8>, // precomputed answers for each block type, (min relative to the first value, argmin) pairs
+}
+
+/// `RMQ` borrowing its arrays, e.g. from a memory mapped file.
+#[derive(Clone, Copy)]
+pub struct RMQView<'a, I = usize> {
+  b : usize,
+  ranges_answers: &'a [I],
+  block_firsts: &'a [I],
+  block_types: &'a [I],
+  block_answers: &'a [u8],
 }
 
 /// Defines how to get an index for a given range WITHIN a block
-pub const fn query_index(left : usize, right : usize) -> usize {
+const fn query_index(left : usize, right : usize) -> usize {
   right*(right+1)/2 + left
 }
 
-impl RMQ {
+/// b ~ log n / c
+fn block_size(n: usize) -> usize {
+  let c = 7;
+  1.max( (n as f64).log2().ceil() as usize / c )
+}
+
+/// Number of 2^k range levels over m blocks.
+fn levels(m: usize) -> usize {
+  ((m as f64).log2().ceil() as usize).max(1)
+}
+
+/// Number of block types and of answers for each of them, for blocks of size b.
+fn block_tables(b: usize) -> (usize, usize) {
+  (3_usize.pow(b as u32), (b+1) * b / 2) // see query_index
+}
+
+impl<'a, I: IndexType> RMQView<'a, I> {
 
   /// argminimum of the range [left, right]
   pub fn query(&self, left : usize, right : usize) -> usize {
@@ -61,8 +96,8 @@ impl RMQ {
       // checking answer in the precomputed answers table for the block
       let l = left % self.b;
       let r = right % self.b;
-      let min = self.block_answers[self.block_types[r_block]][query_index(l, r)];
-      return r_block * self.b + min.argmin;
+      let min = self.block_answer(r_block, l, r);
+      r_block * self.b + min.argmin
     } else {
       // across blocks: |left scraps|RANGE OF BLOCKS|right scraps|
       // Cover the range of blocks with two ranges of size 2^k, read answer,
@@ -70,108 +105,521 @@ impl RMQ {
       let range_min = {
         let log = (((r_block - (l_block+1)) as f64).log2() - 1.0).ceil() as usize;
         if r_block > 1 + l_block {
-          self.ranges_answers[log][l_block+1].min( self.ranges_answers[log][r_block - (1<<log)] )
+          self.range_answer(log, l_block+1).min( self.range_answer(log, r_block - (1<<log)) )
         } else {
           Min::default()
         }
       };
-      let left_scraps = self.block_answers[self.block_types[l_block]][query_index(left % self.b, self.b - 1)];
-      let right_scraps = self.block_answers[self.block_types[r_block]][query_index(0, right % self.b)];
-      let left_scraps = Min::new(((self.block_firsts[l_block] as i64) + left_scraps.min) as usize, left_scraps.argmin + l_block * self.b);
-      let right_scraps = Min::new(((self.block_firsts[r_block] as i64) + right_scraps.min) as usize, right_scraps.argmin + r_block * self.b);
-      left_scraps.min(range_min).min(right_scraps).argmin
+      let left_scraps = self.block_answer(l_block, left % self.b, self.b - 1);
+      let right_scraps = self.block_answer(r_block, 0, right % self.b);
+      let scraps_min = |block: usize, scraps: Min<i64, usize>| Min::new(
+        I::from_usize(((self.block_firsts[block].to_usize() as i64) + scraps.min) as usize),
+        I::from_usize(scraps.argmin + block * self.b));
+      scraps_min(l_block, left_scraps).min(range_min).min(scraps_min(r_block, right_scraps)).argmin.to_usize()
     }
   }
 
-  pub fn create_rmq(values: &Vec<usize>  ) -> RMQ {
+  fn range_answer(&self, level: usize, block: usize) -> Min<I, I> {
+    let k = 2 * (level * self.block_types.len() + block);
+    Min::new(self.ranges_answers[k], self.ranges_answers[k+1])
+  }
+
+  fn block_answer(&self, block: usize, left: usize, right: usize) -> Min<i64, usize> {
+    let (_, s) = block_tables(self.b);
+    let k = 2 * (self.block_types[block].to_usize() * s + query_index(left, right));
+    Min::new(self.block_answers[k] as i8 as i64, self.block_answers[k+1] as usize)
+  }
+
+  /// Checks that the arrays have the shape of a structure over a sequence of length n,
+  /// and if `values` also that the indices in them are in range.
+  pub(crate) fn check(&self, n: usize, values: bool) -> Result<(), PersistError> {
+    let b = block_size(n);
+    let m = n.div_ceil(b);
+    let (types, s) = block_tables(b);
+    let corrupted = |what| Err(PersistError::Corrupted(what));
+    if self.b != b { return corrupted("rmq block size") }
+    if self.ranges_answers.len() != 2 * levels(m) * m || self.block_firsts.len() != m || self.block_types.len() != m {
+      return corrupted("rmq array of a wrong length")
+    }
+    if self.block_answers.len() != 2 * types * s { return corrupted("rmq block answers of a wrong length") }
+    if values {
+      if self.ranges_answers.iter().skip(1).step_by(2).any(|x| x.to_usize() >= n) { return corrupted("rmq answer out of range") }
+      check_below(self.block_types, types, "rmq block type out of range")?;
+      if self.block_answers.iter().skip(1).step_by(2).any(|&x| x as usize >= b) { return corrupted("rmq block answer out of range") }
+    }
+    Ok(())
+  }
+
+  pub(crate) fn write_to<W: Write>(&self, w: &mut Writer<W>) -> io::Result<()> {
+    w.u64(self.b as u64)?;
+    w.array(self.ranges_answers)?;
+    w.array(self.block_firsts)?;
+    w.array(self.block_types)?;
+    w.byte_array(self.block_answers)
+  }
+
+  /// Reads the structure written by `write_to` in place.
+  pub(crate) fn read_from(r: &mut ByteReader<'a>) -> Result<Self, PersistError> {
+    Ok(RMQView {
+      b: r.usize()?,
+      ranges_answers: r.array()?,
+      block_firsts: r.array()?,
+      block_types: r.array()?,
+      block_answers: r.byte_array()?,
+    })
+  }
+
+}
+
+impl<I: IndexType> RMQ<I> {
 
+  /// argminimum of the range [left, right]
+  pub fn query(&self, left : usize, right : usize) -> usize {
+    self.view().query(left, right)
+  }
+
+  pub fn view(&self) -> RMQView<'_, I> {
+    RMQView {
+      b: self.b,
+      ranges_answers: &self.ranges_answers,
+      block_firsts: &self.block_firsts,
+      block_types: &self.block_types,
+      block_answers: &self.block_answers,
+    }
+  }
+
+  /// Builds the structure from a sequence where neighbouring values differ at most by 1.
+  /// Consumes the values block by block, so they don't need to be collected beforehand.
+  pub fn create_rmq(values: impl IntoIterator<Item = usize, IntoIter: ExactSizeIterator>) -> RMQ<I> {
+
+    let mut values = values.into_iter();
     let n = values.len();
-    let c = 7;
-    let b = 1.max( (n as f64).log2().ceil() as usize / c );
-    // chunk into b sized blocks, extend last block to match
-    let vals = values.clone(); // note: would work with 0..n as iterator but with #![feature(iter_array_chunks)]
-    let chunks: Box<dyn Iterator<Item = Vec<usize>>> = {
-      let chunks = vals.chunks_exact(b);
-      let remainder = chunks.remainder();
-      let chunks = chunks.map(|c| Vec::from(c));
-      if ! remainder.is_empty() {
-        let z = remainder.last().unwrap();
-        let last = {
-          let mut last: Vec<usize> = remainder.iter().map(Clone::clone).collect();
-          last.extend(iter::repeat(z).take(b - last.len()));
-          last
-        };
-        Box::new(chunks.chain(iter::once(last)))
-      } else {
-        Box::new(chunks)
-      }
-    };
-  
-    let block_type = |diffs: &Vec<i8>| {
-      diffs.iter().fold(0, |acc, diff| acc * 3 + ((diff + 1) as usize))
-    };
-
-    let (block_mins, block_firsts, block_difftypes): (Vec<Min<usize, usize>>, Vec<usize>, Vec<Vec<i8>>) = chunks.map(|chunk| {
-      let min = chunk.iter().enumerate().map(|(i, min)| Min::new(*min, i))
-        .fold(Min::default(), Min::min);
-      let first = chunk[0];
+    let b = block_size(n);
+    let m = n.div_ceil(b); // number of blocks
+    let logm = levels(m);
+    let (r, s) = block_tables(b); // "Expecting <number of distinct blocks (somesmallpoly(n)> to fit in usize")
+
+    // answers for 2^k sized ranges, flat by level and block, filled bottom up
+    let mut ranges_answers = vec![I::default(); 2 * logm * m];
+    let mut block_firsts = Vec::with_capacity(m);
+    let mut block_types = Vec::with_capacity(m);
+    // precomputed answers for within block queries, relative values within a block are at most b in absolute value
+    let mut block_answers = vec![0u8; 2 * r * s];
+    let mut types_done = vec![false; r];
+
+    // chunk into b sized blocks, extend last block to match, one block at a time
+    let mut difftype = vec![0i8; b];
+    for block in 0..m {
+      let first = values.next().unwrap();
       let mut prev = first;
-      let mut difftype = vec![0];
-      for a in &chunk[1..] {
-        let diff = (( *a as i64) - (prev as i64)) as i8;
-        assert!(-1 <= diff && diff <= 1, "diff by -1/0/+1");
-        difftype.push(diff);
-        prev = *a;
-      }
-      (min, first, difftype)
-    }).unzip3();
-    let m = block_mins.len(); // n / b
-    let logm = ((m as f64).log2().ceil() as usize).max(1 as usize);
-    
-    let pow2 = |x: usize| 1 << x;
-    // calculate ranges bottom up
-    let mut range_answers = vec![vec![Min::default(); m]; logm];
-    for i in 0..m { range_answers[0][i] = Min::new(block_mins[i].min, i * b + block_mins[i].argmin) }
-    for i in 1..logm {
-      for j in 0..m {
-        range_answers[i][j] = range_answers[i-1][j].min( *range_answers[i-1].get( j + pow2(i-1) ).unwrap_or(&Min::default()) );
+      let mut min = Min::new(first, 0);
+      for (i, diff) in difftype.iter_mut().enumerate().skip(1) {
+        let a = if block * b + i < n { values.next().unwrap() } else { prev };
+        let d = (a as i64) - (prev as i64);
+        assert!((-1..=1).contains(&d), "diff by -1/0/+1");
+        *diff = d as i8;
+        min = min.min(Min::new(a, i));
+        prev = a;
       }
-    }
-  
-    // precompute answers for within block queries
-    let r: usize = (3 as usize).pow(b as u32); // "Expecting <number of distinct blocks (somesmallpoly(n)> to fit in usize")
-    let s = (b+1) * b / 2; // see query_index
-    let mut block_answers = vec![vec![Min::default(); s]; r];
-    let mut types_done = vec![false; r];
-    let mut block_types = vec![0; m];
-    for i in 0..m {
-      let bl_difftype = &block_difftypes[i]; 
-      let bl_type = block_type(bl_difftype);
+      ranges_answers[2 * block] = I::from_usize(min.min);
+      ranges_answers[2 * block + 1] = I::from_usize(block * b + min.argmin);
+      block_firsts.push(I::from_usize(first));
+
+      let bl_type = difftype.iter().fold(0, |acc, diff| acc * 3 + ((diff + 1) as usize));
       if ! types_done[bl_type] {
         // O(b^2) computation of all range mins
         let mut val_at_x: i64 = 0;
         for x in 0..b { // start
           let mut val_at_y = val_at_x;
-          val_at_x += bl_difftype[x] as i64;
+          val_at_x += difftype[x] as i64;
           let mut min = Min::new(val_at_x, x);
-          for y in x..b { // end
-            val_at_y += bl_difftype[y] as i64;
+          for (y, &diff) in difftype.iter().enumerate().skip(x) { // end
+            val_at_y += diff as i64;
             min = min.min(Min::new(val_at_y, y));
-            block_answers[bl_type][query_index(x, y)] = min;
+            let k = 2 * (bl_type * s + query_index(x, y));
+            block_answers[k] = min.min as i8 as u8;
+            block_answers[k+1] = min.argmin as u8;
           }
         }
+        types_done[bl_type] = true;
       }
-      block_types[i] = bl_type;
-      types_done[bl_type] = true;
+      block_types.push(I::from_usize(bl_type));
+    }
+
+    let pow2 = |x: usize| 1 << x;
+    let mut rmq = RMQ { b, ranges_answers, block_firsts, block_types, block_answers };
+    // calculate ranges bottom up
+    for i in 1..logm {
+      for j in 0..m {
+        let next = if j + pow2(i-1) < m { rmq.view().range_answer(i-1, j + pow2(i-1)) } else { Min::default() };
+        let answer = rmq.view().range_answer(i-1, j).min(next);
+        let k = 2 * (i * m + j);
+        rmq.ranges_answers[k] = answer.min;
+        rmq.ranges_answers[k+1] = answer.argmin;
+      }
+    }
+    rmq
+  }
+
+  /// Reads the structure written by `RMQView::write_to`.
+  pub(crate) fn read_from<R: Read>(r: &mut Reader<R>, n: usize) -> Result<Self, PersistError> {
+    let b = block_size(n);
+    let m = n.div_ceil(b);
+    let (types, s) = block_tables(b);
+    if r.usize()? != b {
+      return Err(PersistError::Corrupted("rmq block size"));
+    }
+    Ok(RMQ {
+      b,
+      ranges_answers: r.array(2 * levels(m) * m)?,
+      block_firsts: r.array(m)?,
+      block_types: r.array(m)?,
+      block_answers: r.byte_array(2 * types * s)?,
+    })
+  }
+
+}
+
+/// Range minimum queries over a sequence fixed at creation, e.g. the lcp array, for which it is the backend of `SARMQ`.
+/// Implemented by `EulerRMQ`, the default, `SparseTableRMQ`, `SqrtRMQ` and `SuccinctRMQ`, which trade query time for space.
+/// The structures don't keep the values, the queries are given the same ones as `build`.
+pub trait RangeMinQuery<I: IndexType = usize> {
+  /// Borrowed form answering the same queries, as held by `SARMQView`.
+  type View<'a>: RangeMinView<I> + 'a where Self: 'a;
+  /// Builds the structure over the values.
+  fn build(values: &[I]) -> Self;
+  /// Index of the leftmost minimum of `values[left..=right]`.
+  fn query(&self, values: &[I], left: usize, right: usize) -> usize;
+  fn view(&self) -> Self::View<'_>;
+}
+
+/// `RangeMinQuery` borrowed by a view, `EulerRMQView` for the default one and a reference for the others.
+pub trait RangeMinView<I: IndexType = usize>: Copy {
+  /// Index of the leftmost minimum of `values[left..=right]`.
+  fn query(&self, values: &[I], left: usize, right: usize) -> usize;
+}
+
+impl<I: IndexType, R: RangeMinQuery<I>> RangeMinView<I> for &R {
+  fn query(&self, values: &[I], left: usize, right: usize) -> usize {
+    R::query(self, values, left, right)
+  }
+}
+
+/// The ±1 block scheme for any values: `RMQ` on the depths along the Euler tour of the cartesian tree,
+/// as in `CartesianRMQ`, without the values and with indices stored as I.
+/// O(n) space and creation time, O(1) query time.
+pub struct EulerRMQ<I = usize> {
+  first_occ: Vec<I>, // position of each index in the euler tour
+  euler: Vec<I>, // indices along the euler tour
+  rmq: RMQ<I>,
+}
+
+/// `EulerRMQ` borrowing its arrays, e.g. from a memory mapped file.
+#[derive(Clone, Copy)]
+pub struct EulerRMQView<'a, I = usize> {
+  first_occ: &'a [I],
+  euler: &'a [I],
+  rmq: RMQView<'a, I>,
+}
+
+impl<'a, I: IndexType> EulerRMQView<'a, I> {
+
+  /// Index of the leftmost minimum of the range [left, right].
+  pub fn query(&self, left: usize, right: usize) -> usize {
+    let (l, r) = (self.first_occ[left].to_usize(), self.first_occ[right].to_usize());
+    self.euler[self.rmq.query(l.min(r), l.max(r))].to_usize()
+  }
+
+  /// Checks that the arrays have the shape of a structure over a sequence of length n,
+  /// and if `values` also that the indices in them are in range.
+  pub(crate) fn check(&self, n: usize, values: bool) -> Result<(), PersistError> {
+    let euler_len = (2 * n).saturating_sub(1);
+    if self.first_occ.len() != n || self.euler.len() != euler_len {
+      return Err(PersistError::Corrupted("array of a wrong length"));
+    }
+    self.rmq.check(euler_len, values)?;
+    if values {
+      check_below(self.first_occ, euler_len, "euler tour index out of range")?;
+      check_below(self.euler, n, "euler tour node out of range")?;
+    }
+    Ok(())
+  }
+
+  pub(crate) fn write_to<W: Write>(&self, w: &mut Writer<W>) -> io::Result<()> {
+    w.array(self.first_occ)?;
+    w.array(self.euler)?;
+    self.rmq.write_to(w)
+  }
+
+  /// Reads the structure written by `write_to` in place.
+  pub(crate) fn read_from(r: &mut ByteReader<'a>) -> Result<Self, PersistError> {
+    Ok(EulerRMQView { first_occ: r.array()?, euler: r.array()?, rmq: RMQView::read_from(r)? })
+  }
+
+}
+
+impl<I: IndexType> RangeMinView<I> for EulerRMQView<'_, I> {
+  fn query(&self, _values: &[I], left: usize, right: usize) -> usize {
+    EulerRMQView::query(self, left, right)
+  }
+}
+
+impl<I: IndexType> EulerRMQ<I> {
+
+  pub fn view(&self) -> EulerRMQView<'_, I> {
+    EulerRMQView { first_occ: &self.first_occ, euler: &self.euler, rmq: self.rmq.view() }
+  }
+
+  /// Reads the structure written by `EulerRMQView::write_to`, over a sequence of length n.
+  pub(crate) fn read_from<R: Read>(r: &mut Reader<R>, n: usize) -> Result<Self, PersistError> {
+    let euler_len = n.saturating_mul(2).saturating_sub(1);
+    Ok(EulerRMQ { first_occ: r.array(n)?, euler: r.array(euler_len)?, rmq: RMQ::read_from(r, euler_len)? })
+  }
+
+}
+
+impl<I: IndexType> RangeMinQuery<I> for EulerRMQ<I> {
+  type View<'a> = EulerRMQView<'a, I>;
+
+  fn build(values: &[I]) -> Self {
+    // ties broken by position, so that the leftmost one is the ancestor of the others
+    let tree = sa::cartesian_tree_by::<I>(values.len(), |i, j| values[i].cmp(&values[j]).then(i.cmp(&j)));
+    let euler_walk = tree.euler_walk();
+    let rmq = RMQ::create_rmq(euler_walk.euler.iter().map(|&x| euler_walk.depth[x.to_usize()].to_usize()));
+    EulerRMQ { first_occ: euler_walk.first_occ, euler: euler_walk.euler, rmq }
+  }
+
+  fn query(&self, _values: &[I], left: usize, right: usize) -> usize {
+    EulerRMQ::view(self).query(left, right)
+  }
+
+  fn view(&self) -> Self::View<'_> {
+    EulerRMQ::view(self)
+  }
+}
+
+/// Sparse table: the leftmost minimum of every range of a power of two length, two of which cover any range.
+/// O(n log n) space and creation time, O(1) query time with no indirection.
+pub struct SparseTableRMQ<I = usize> {
+  // argmins of the ranges of length 2^k, by k and start
+  levels: Vec<Vec<I>>,
+}
+
+impl<I: IndexType> RangeMinQuery<I> for SparseTableRMQ<I> {
+  type View<'a> = &'a Self;
+
+  fn build(values: &[I]) -> Self {
+    let n = values.len();
+    let mut levels: Vec<Vec<I>> = vec![(0..n).map(I::from_usize).collect()];
+    let mut len = 1;
+    while 2 * len <= n {
+      let prev = levels.last().unwrap();
+      let level = (0..=n - 2 * len).map(|i| {
+        let (a, b) = (prev[i], prev[i + len]);
+        if values[b.to_usize()] < values[a.to_usize()] { b } else { a }
+      }).collect();
+      levels.push(level);
+      len *= 2;
     }
-  
-    RMQ {
-      b : b,
-      ranges_answers: range_answers,
-      block_firsts: block_firsts,
-      block_types: block_types,
-      block_answers: block_answers,
+    SparseTableRMQ { levels }
+  }
+
+  fn query(&self, values: &[I], left: usize, right: usize) -> usize {
+    assert!(left <= right && right < values.len(), "Range [{left}, {right}] is not within 0..{}.", values.len());
+    let k = (right - left + 1).ilog2() as usize;
+    let (a, b) = (self.levels[k][left].to_usize(), self.levels[k][right + 1 - (1 << k)].to_usize());
+    if values[b] < values[a] { b } else { a }
+  }
+
+  fn view(&self) -> &Self {
+    self
+  }
+}
+
+/// Square root decomposition: the leftmost minimum of every block of about sqrt n values, the rest is scanned.
+/// O(sqrt n) space, O(n) creation time, O(sqrt n) query time.
+pub struct SqrtRMQ<I = usize> {
+  b: usize,
+  block_mins: Vec<I>,
+}
+
+impl<I: IndexType> RangeMinQuery<I> for SqrtRMQ<I> {
+  type View<'a> = &'a Self;
+
+  fn build(values: &[I]) -> Self {
+    let b = values.len().isqrt().max(1);
+    let block_mins = values.chunks(b).enumerate().map(|(block, chunk)| {
+      I::from_usize(block * b + leftmost_min(chunk, 0..chunk.len()))
+    }).collect();
+    SqrtRMQ { b, block_mins }
+  }
+
+  fn query(&self, values: &[I], left: usize, right: usize) -> usize {
+    assert!(left <= right && right < values.len(), "Range [{left}, {right}] is not within 0..{}.", values.len());
+    let (l_block, r_block) = (left / self.b, right / self.b);
+    if r_block <= l_block + 1 {
+      return leftmost_min(values, left..right + 1);
     }
+    // |left scraps|whole blocks|right scraps|, each candidate to the right of the previous ones
+    let candidates = iter::once(leftmost_min(values, left..(l_block + 1) * self.b))
+      .chain(self.block_mins[l_block + 1..r_block].iter().map(|x| x.to_usize()))
+      .chain(iter::once(leftmost_min(values, r_block * self.b..right + 1)));
+    candidates.reduce(|best, i| if values[i] < values[best] { i } else { best }).unwrap()
+  }
+
+  fn view(&self) -> &Self {
+    self
+  }
+}
+
+fn leftmost_min<I: Ord>(values: &[I], range: std::ops::Range<usize>) -> usize {
+  range.reduce(|best, i| if values[i] < values[best] { i } else { best }).unwrap()
+}
+
+impl<I: IndexType> RangeMinQuery<I> for SuccinctRMQ {
+  type View<'a> = &'a Self;
+
+  fn build(values: &[I]) -> Self {
+    SuccinctRMQ::create_succinct_rmq(values)
+  }
+
+  fn query(&self, _values: &[I], left: usize, right: usize) -> usize {
+    SuccinctRMQ::query(self, left, right)
   }
 
-}
\ No newline at end of file
+  fn view(&self) -> &Self {
+    self
+  }
+}
+
+/// Decides which value of a range is reported, e.g. the minimum, the maximum or the minimum by a key.
+pub trait Selector<T> {
+  /// Order in which the selected value is the least one.
+  fn cmp(&self, a: &T, b: &T) -> Ordering;
+  /// Of equal values the leftmost one is selected, unless this returns false.
+  fn leftmost(&self) -> bool {
+    true
+  }
+}
+
+/// Selects the leftmost minimum.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Minimum;
+
+/// Selects the leftmost maximum.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Maximum;
+
+/// Selects the rightmost value instead of the leftmost one, e.g. `Rightmost(Minimum)`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rightmost<S>(pub S);
+
+/// Selects the leftmost minimum by a key, e.g. `ByKey(|x: &(u32, u32)| x.1)`.
+#[derive(Debug, Clone, Copy)]
+pub struct ByKey<F>(pub F);
+
+/// Selects the leftmost least value according to a comparator.
+#[derive(Debug, Clone, Copy)]
+pub struct ByCmp<F>(pub F);
+
+impl<T: Ord> Selector<T> for Minimum {
+  fn cmp(&self, a: &T, b: &T) -> Ordering {
+    a.cmp(b)
+  }
+}
+
+impl<T: Ord> Selector<T> for Maximum {
+  fn cmp(&self, a: &T, b: &T) -> Ordering {
+    b.cmp(a)
+  }
+}
+
+impl<T, S: Selector<T>> Selector<T> for Rightmost<S> {
+  fn cmp(&self, a: &T, b: &T) -> Ordering {
+    self.0.cmp(a, b)
+  }
+  fn leftmost(&self) -> bool {
+    ! self.0.leftmost()
+  }
+}
+
+impl<T, K: Ord, F: Fn(&T) -> K> Selector<T> for ByKey<F> {
+  fn cmp(&self, a: &T, b: &T) -> Ordering {
+    self.0(a).cmp(&self.0(b))
+  }
+}
+
+impl<T, F: Fn(&T, &T) -> Ordering> Selector<T> for ByCmp<F> {
+  fn cmp(&self, a: &T, b: &T) -> Ordering {
+    self.0(a, b)
+  }
+}
+
+/// Value chosen by a selector in a range, with its position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selected<A> {
+  pub value: A,
+  pub index: usize,
+}
+
+/// Range queries on a sequence of any values, reporting the value chosen by the selector, by default the leftmost minimum.
+/// The selected value of a range is the lowest common ancestor of its ends in the cartesian tree,
+/// so it is found with RMQ on the depths along the Euler tour of the tree, which differ by 1.
+/// O(n) space and creation time, O(1) query time.
+pub struct CartesianRMQ<T, S = Minimum> {
+  values: Vec<T>,
+  selector: S,
+  first_occ: Vec<usize>, // position of each index in the euler tour
+  euler: Vec<usize>, // indices along the euler tour
+  rmq: RMQ,
+}
+
+impl<T: Ord> CartesianRMQ<T> {
+
+  /// Range minimum queries. Takes a Vec, or copies a slice.
+  pub fn create_cartesian_rmq(values: impl Into<Vec<T>>) -> Self {
+    Self::create_with_selector(values, Minimum)
+  }
+
+}
+
+impl<T, S: Selector<T>> CartesianRMQ<T, S> {
+
+  /// Range queries for the values chosen by the selector. Takes a Vec, or copies a slice.
+  pub fn create_with_selector(values: impl Into<Vec<T>>, selector: S) -> Self {
+    let values = values.into();
+    // ties broken by position, so that the selected one is the ancestor of the others
+    let leftmost = selector.leftmost();
+    let tree = sa::cartesian_tree_by::<usize>(values.len(), |i, j| {
+      selector.cmp(&values[i], &values[j]).then(if leftmost { i.cmp(&j) } else { j.cmp(&i) })
+    });
+    let euler_walk = tree.euler_walk();
+    let rmq = RMQ::create_rmq(euler_walk.euler.iter().map(|&x| euler_walk.depth[x]));
+    CartesianRMQ { values, selector, first_occ: euler_walk.first_occ, euler: euler_walk.euler, rmq }
+  }
+
+  /// selected value of the range [left, right] (the minimum by default) with its position
+  pub fn query(&self, left: usize, right: usize) -> Selected<&T> {
+    assert!(left <= right && right < self.values.len(), "Range [{left}, {right}] is not within 0..{}.", self.values.len());
+    let (l, r) = (self.first_occ[left], self.first_occ[right]);
+    let index = self.euler[self.rmq.query(l.min(r), l.max(r))];
+    Selected { value: &self.values[index], index }
+  }
+
+  pub fn selector(&self) -> &S {
+    &self.selector
+  }
+
+  pub fn values(&self) -> &[T] {
+    &self.values
+  }
+
+  pub fn len(&self) -> usize {
+    self.values.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.values.is_empty()
+  }
+
+}
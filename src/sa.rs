@@ -1,116 +1,133 @@
+use std::cmp::Ordering;
 use std::vec;
 
-pub struct SA {
-  pub sa: Vec<usize>,
-  pub sa_inverse: Vec<usize>,
+use crate::index::{check_len, IndexType};
+use crate::SarmqError;
+
+/// Suffix array of a sequence together with its inverse.
+/// Indices are stored as I, e.g. `SA<u32>` for sequences shorter than 2^32.
+pub struct SA<I = usize> {
+  pub sa: Vec<I>,
+  pub sa_inverse: Vec<I>,
 }
 
-impl SA {
+impl<I: IndexType> SA<I> {
 
   /// Calculates the lexicographical order on suffixes of a.
   /// O(n) time and space.
-  /// Assumes integers in 0..n range.
-  pub fn create_suffix_array(a: &Vec<usize>) -> Self {
+  /// Assumes integers in 0..n range, and n fitting in I.
+  pub fn create_suffix_array(a: &[impl IndexType]) -> Self {
     // let mut s: Vec<usize> = (0 .. a.len()).collect();
     // s.sort_by_key(|&i| &a[i..]);
-    let s = sa(a);
+    let sa: Vec<I> = sa(a);
     // assert_eq!(ss, s);
-    let mut sa_inverse: Vec<usize> = vec![0; a.len()];
-    s.iter().enumerate().for_each(|(i, x)| sa_inverse[*x] = i);
-    SA {
-      sa: s,
-      sa_inverse,
-    }
+    let mut sa_inverse: Vec<I> = vec![I::default(); a.len()];
+    sa.iter().enumerate().for_each(|(i, x)| sa_inverse[x.to_usize()] = I::from_usize(i));
+    SA { sa, sa_inverse }
+  }
+
+  /// Like `create_suffix_array`, but reports symbols outside of 0..n range
+  /// or a sequence too long for I instead of panicking.
+  pub fn try_create_suffix_array(a: &[impl IndexType]) -> Result<Self, SarmqError> {
+    check_len(a.len(), I::MAX)?;
+    check_alphabet(a)?;
+    Ok(Self::create_suffix_array(a))
   }
 
 }
 
+/// Checks that a contains integers in 0..n range, as assumed by the suffix array construction.
+pub fn check_alphabet(a: &[impl IndexType]) -> Result<(), SarmqError> {
+  let n = a.len();
+  match a.iter().position(|&x| x.to_usize() >= n) {
+    Some(index) => Err(SarmqError::SymbolOutOfRange { index, symbol: a[index].to_usize(), n }),
+    None => Ok(()),
+  }
+}
+
 /// sort suffixes of xs in O(n)
-/// assumes xs contains numbers in range 0..n
-fn sa(xs : &Vec<usize>) -> Vec<usize> {
+/// assumes xs contains numbers in range 0..n, and n+2 fitting in I
+fn sa<I: IndexType, T: IndexType>(xs : &[T]) -> Vec<I> {
   // sort recursively suffixes of triplets mod3=0 and mod3=1,
   // then merge with mod3=2 suffixes
   let n = xs.len();
 
   let c= 10;
   if n < c {
-    let mut r: Vec<usize> = (0..n).collect();
-    r.sort_by_key(|&i| &xs[i..]);
+    let mut r: Vec<I> = (0..n).map(I::from_usize).collect();
+    r.sort_by_key(|&i| &xs[i.to_usize()..]);
     return r;
   }
 
-  fn sort_triples(n : usize, triples : &Vec<(usize, usize, usize)>) -> (Vec<usize>, Vec<usize>) {
-    let phase = |xs : Vec<usize>, pi : Box<dyn Fn((usize, usize, usize)) -> usize>| {
-      let mut vs = vec![ vec![] ; n];
-      for i in xs {
-        vs[pi(triples[i])].push(i);
-      }
-      vs.concat()
-    };
-    let ph2 = phase((0..(triples.len())).collect(), Box::new(|x| x.2));
-    let ph1 = phase(ph2, Box::new(|x| x.1));
-    let ph0 = phase(ph1, Box::new(|x| x.0));
-    // now collapse equal triples
-    let mut group_id = 0;
-    let mut groups = vec![0; ph0.len()];
-    if !ph0.is_empty() {
-      groups[ph0[0]] = 0;
-      for i in 1..ph0.len() {
-        if triples[ph0[i]] != triples[ph0[i - 1]] {
-          group_id += 1;
-        }
-        groups[ph0[i]] = group_id;
-      }
+  // stable sort of order by keys in 0..keys, one count array and one output array per pass
+  fn counting_sort<I: IndexType>(order: &[I], keys: usize, key: impl Fn(usize) -> usize) -> Vec<I> {
+    let mut starts = vec![I::default(); keys + 1];
+    for &i in order {
+      let count = &mut starts[key(i.to_usize()) + 1];
+      *count = I::from_usize(count.to_usize() + 1);
     }
-    (ph0, groups) // (order, assignment (ranks but repeating for equal))
-  }
-  fn sort_tuples(n : usize, tuples : &Vec<(usize, usize)>) -> (Vec<usize>, Vec<usize>) {
-    sort_triples(n, &tuples.iter().map(|(a, b)| (*a, *b, 0)).collect::<Vec<_>>())
+    for c in 1..=keys {
+      starts[c] = I::from_usize(starts[c].to_usize() + starts[c - 1].to_usize());
+    }
+    let mut sorted = vec![I::default(); order.len()];
+    for &i in order {
+      let start = &mut starts[key(i.to_usize())];
+      sorted[start.to_usize()] = i;
+      *start = I::from_usize(start.to_usize() + 1);
+    }
+    sorted
   }
-  fn invert(per : &Vec<usize>) -> Vec<usize> {
-    let mut ys = vec![0; per.len()];
+  fn invert<I: IndexType>(per : &[I]) -> Vec<I> {
+    let mut ys = vec![I::default(); per.len()];
     for (i, x) in per.iter().enumerate() {
-      ys[*x] = i;
+      ys[x.to_usize()] = I::from_usize(i);
     }
     ys
   }
 
-  let triples : Vec<(usize, usize, usize)> = {
-    let into_triple = |c: &[usize]| {
-      let mut c = c.iter();
-      let a = *c.next().unwrap();
-      let b = *c.next().unwrap_or(&0);
-      let c = *c.next().unwrap_or(&0);
-      (a, b, c)
-    };
-    xs.chunks(3).map(into_triple)
-      .chain(
-    xs[1..].chunks(3).map(into_triple)).collect()
-  };
+  // shifted by 1, so that the padding 0 is smaller than any symbol
+  let get = |xs : &[T], i: usize| {xs.get(i).map(|&x|x.to_usize()+1).unwrap_or(0)};
 
-  // need to move by 1, to use 0 as -inf in between mod3=0 and mod3=1 suffixes
-  let (_, triples) = sort_triples(n+1, &triples);
-  let mut triples = triples.iter().map(|&x| x+1).collect::<Vec<usize>>();
+  // triples starting at mod3=0 positions, then at mod3=1 positions, read from xs rather than stored
   let k = n.div_ceil(3); // middle
-  triples.insert(k, 0); // insert a "#" splitter
+  let m = k + (n-1).div_ceil(3);
+  let start = |j: usize| if j < k {3*j} else {3*(j-k) + 1};
+  let triple = |j: usize| (get(xs, start(j)), get(xs, start(j)+1), get(xs, start(j)+2));
+  let mut order: Vec<I> = (0..m).map(I::from_usize).collect();
+  for d in (0..3).rev() {
+    order = counting_sort(&order, n+1, |j| get(xs, start(j)+d));
+  }
+
+  // name the triples by their ranks, equal ones alike, moved by 1 to use 0 as -inf
+  // in between mod3=0 and mod3=1 suffixes, for which a "#" splitter is inserted at k
+  let mut names = vec![I::default(); m+1];
+  let mut name = 0;
+  for (r, &j) in order.iter().enumerate() {
+    let j = j.to_usize();
+    if r > 0 && triple(j) != triple(order[r-1].to_usize()) {
+      name += 1;
+    }
+    names[if j < k {j} else {j+1}] = I::from_usize(name+1);
+  }
+  drop(order);
 
-  let mod01: Vec<usize> = sa(&triples);
+  let mod01: Vec<I> = sa(&names);
+  drop(names);
 
   let ranks = invert(&mod01);
   let index = |i: usize| { // i-th suffix in sorted order
-    if i % 3 == 0 {i/3} else {k + 1 + (i-1)/3}
+    if i.is_multiple_of(3) {i/3} else {k + 1 + (i-1)/3}
   };
   let rev_index = |j: usize| { // j-th sorted suffix
     if j < k {3*j} else {3*(j-k-1) + 1}
   };
-  let get = |xs : &Vec<usize>, i: usize| {xs.get(i).map(|&r|r+1).unwrap_or(0)};
-  let rank = |i: usize| {get(&ranks, index(i))};
+  let rank = |i: usize| {ranks.get(index(i)).map(|&r|r.to_usize()+1).unwrap_or(0)};
   // compare suffix l-th with r-th where l % 3 = 2
   let cmp = |l:usize,r:usize| {
     // empty =0, nonempty >=1
-    let l0 = get(&xs, l);
-    let r0 = get(&xs, r);
-    if r % 3 == 0 || r % 3 == 2 {
+    let l0 = get(xs, l);
+    let r0 = get(xs, r);
+    if r.is_multiple_of(3) || r % 3 == 2 {
       (l0, rank(l+1)).le(&(r0, rank(r+1)))
     } else { // r % 3 = 1
       let l1 = get(xs, l+1);
@@ -119,134 +136,140 @@ fn sa(xs : &Vec<usize>) -> Vec<usize> {
     }
   };
 
-  // sorting mod=2 suffixes
-  let (mod2, _)= sort_tuples(n+1,&(0..((n-2).div_ceil(3))).map(|i| (xs[3*i+2], rank(3*i+3))).collect::<Vec<(usize, usize)>>());
+  // sorting mod=2 suffixes, by the rank of the suffix after the symbol and then by the symbol
+  let mod2: Vec<I> = (0..((n-2).div_ceil(3))).map(I::from_usize).collect();
+  let mod2 = counting_sort(&mod2, m+2, |i| rank(3*i+3));
+  let mod2 = counting_sort(&mod2, n, |i| xs[3*i+2].to_usize());
 
   // merging
-  let mut res = vec![];
-  let mut mod2 = mod2.iter().map(|i| 3*i + 2).collect::<Vec<usize>>();
-  let mut mod01 = mod01.into_iter().filter_map(|i| if i != k {Some(rev_index(i))} else {None}).collect::<Vec<usize>>();
-  mod2.reverse();
-  mod01.reverse();
-  while ! mod2.is_empty() && ! mod01.is_empty() {
-    let l = *mod2.last().unwrap();
-    let r = *mod01.last().unwrap();
+  let mut res = Vec::with_capacity(n);
+  let mut mod2 = mod2.iter().map(|i| 3*i.to_usize() + 2).peekable();
+  let mut mod01 = mod01.iter().map(|i| i.to_usize()).filter(|&i| i != k).map(rev_index).peekable();
+  while let (Some(&l), Some(&r)) = (mod2.peek(), mod01.peek()) {
     // assert!(l < n && r < n, "indices within range: {:?} {:?}", l, r);
     if cmp(l, r) {
-      res.push(l);
-      mod2.pop();
+      res.push(I::from_usize(l));
+      mod2.next();
     } else {
-      res.push(r);
-      mod01.pop();
+      res.push(I::from_usize(r));
+      mod01.next();
     }
   }
-  mod2.reverse();
-  mod01.reverse();
-  res.extend(mod2.into_iter());
-  res.extend(mod01.into_iter());
-  
-  return res;
+  res.extend(mod2.chain(mod01).map(I::from_usize));
+
+  res
 }
 
 /// Calculates array lcp, st. lcp(i) = lcp(SA[i],SA[i+1]).
 /// Uses the fact that lcp[SA_inv[i]] − 1 ≤ lcp[SA_inv[i + 1]]
 /// O(n) time, O(1) extra space
-pub fn lcp(a: &Vec<usize>, sa: &SA) -> Vec<usize> {
+pub fn lcp<T: Eq, I: IndexType>(a: &[T], sa: &SA<I>) -> Vec<I> {
   let n = a.len();
   let mut lcp_acc = 0;
-  let mut lcp = vec![0; n];
+  let mut lcp = vec![I::default(); n];
   for i in 0..n {
     // calculating lcp between i and i+1 suffixes of SA
     // starting value of lcp_acc
-    if sa.sa_inverse[i] == n - 1 {
+    let rank = sa.sa_inverse[i].to_usize();
+    if rank == n - 1 {
       lcp_acc = 0; // lcp[n-1] is unused really
     } else {
-      let j = sa.sa[sa.sa_inverse[i]+1];
+      let j = sa.sa[rank+1].to_usize();
       while i+lcp_acc < n && j+lcp_acc < n && a[i+lcp_acc] == a[j+lcp_acc] {
         lcp_acc += 1;
       }
     }
-    lcp[sa.sa_inverse[i]]= lcp_acc;
-    if lcp_acc > 0 {lcp_acc -= 1}
+    lcp[rank] = I::from_usize(lcp_acc);
+    lcp_acc = lcp_acc.saturating_sub(1);
   }
   lcp
 }
 
-/// Bin tree in array form.
-pub struct BinTree {
+/// Bin tree in array form, with nodes stored as I.
+pub struct BinTree<I = usize> {
   pub root : usize,
-  pub lefts : Vec<Option<usize>>,
-  pub rights : Vec<Option<usize>>,
+  pub lefts : Vec<Option<I>>,
+  pub rights : Vec<Option<I>>,
 }
 
 /// Traversal order on a binary tree where a node is visited up to three times, when entering and then when re-entering from its kids
 /// with mapped first occurences of each node and depths. 
-pub struct Euler {
-  pub euler: Vec<usize>,
-  pub first_occ: Vec<usize>,
-  pub depth: Vec<usize>,
+pub struct Euler<I = usize> {
+  pub euler: Vec<I>,
+  pub first_occ: Vec<I>,
+  pub depth: Vec<I>,
 }
 
-impl BinTree {
+impl<I: IndexType> BinTree<I> {
   /// produces traversal order where a node is visited up to three times, when entering and then when re-entering from its kids
-  pub fn euler_walk(&self) -> Euler {
-    let mut depth: Vec<usize> = vec![0; self.lefts.len()];
-    let mut first_occ = vec![0; self.lefts.len()];
-    let mut euler = vec![];
-    let mut stack = vec![(self.root, true)]; // true for entering, false for re-entering
-    depth[self.root] = 0;
-    while ! stack.is_empty() {
-      let (x, entering)= stack.pop().unwrap(); 
+  pub fn euler_walk(&self) -> Euler<I> {
+    let n = self.lefts.len();
+    let mut depth: Vec<I> = vec![I::default(); n];
+    let mut first_occ = vec![I::default(); n];
+    let mut euler = Vec::with_capacity((2 * n).saturating_sub(1));
+    if self.lefts.is_empty() {
+      return Euler { euler, first_occ, depth };
+    }
+    let mut stack = vec![(I::from_usize(self.root), true)]; // true for entering, false for re-entering
+    while let Some((x, entering)) = stack.pop() {
       euler.push(x);
+      let x = x.to_usize();
       if entering {
-        first_occ[x] = euler.len()-1;
+        first_occ[x] = I::from_usize(euler.len()-1);
+        let kid_depth = I::from_usize(depth[x].to_usize() + 1);
         if let Some(right) = self.rights[x] {
-          stack.push((x, false));
+          stack.push((I::from_usize(x), false));
           stack.push((right, true));
-          depth[right] = depth[x] + 1;
+          depth[right.to_usize()] = kid_depth;
         }
         if let Some(left) = self.lefts[x] {
-          stack.push((x, false));
+          stack.push((I::from_usize(x), false));
           stack.push((left, true));
-          depth[left] = depth[x] + 1;
+          depth[left.to_usize()] = kid_depth;
         }
       }
     }
-    return Euler { euler: euler, first_occ: first_occ, depth: depth };
+    Euler { euler, first_occ, depth }
   }
 }
 
 /// Cartesian tree on 0..n by keys: Bin(minimum in a, cartesian tree of a[0..min_index), cartesian tree of a[min_index+1..n))
 /// O(n) time, O(1) extra space
-pub fn cartesian_tree(keys: &Vec<usize>) -> BinTree {
+pub fn cartesian_tree<I: IndexType, T: Ord>(keys: &[T]) -> BinTree<I> {
+  cartesian_tree_by(keys.len(), |i, j| keys[i].cmp(&keys[j]))
+}
+
+/// Like `cartesian_tree`, for keys of 0..n compared with cmp.
+/// Of equal keys the rightmost one becomes the ancestor.
+pub fn cartesian_tree_by<I: IndexType>(n: usize, cmp: impl Fn(usize, usize) -> Ordering) -> BinTree<I> {
   // go from left to right, putting the new node either as new root or somewhere along the rightmost path
   // note: using 0..n as nodes, but keys[xs[i]] as corresponding keys, then putting xs[i] in the final result also
-  let n = keys.len();
-  let mut parent: Vec<usize> = vec![0; n];
+  let le = |i, j| cmp(i, j) != Ordering::Greater; // keys[i] <= keys[j]
+  let mut parent: Vec<I> = vec![I::default(); n];
   let mut is_left = vec![true; n];
   let mut root = 0;
   let mut rightmost = 0;
   for i in 1..n {
     // let x = xs[i];
-    if keys[i] <= keys[root] {
-      parent[root] = i;
+    if le(i, root) {
+      parent[root] = I::from_usize(i);
       root = i;
       rightmost = root;
     } else {
       if rightmost == root {
         // no right kid in root
-        parent[i] = root;
+        parent[i] = I::from_usize(root);
         is_left[i] = false;
-      } else if keys[rightmost] >= keys[i] {
-        while keys[parent[rightmost]] >= keys[i]  { // surely false when we parent[rightmost] = root
-          rightmost = parent[rightmost];
+      } else if le(i, rightmost) {
+        while le(i, parent[rightmost].to_usize())  { // surely false when we parent[rightmost] = root
+          rightmost = parent[rightmost].to_usize();
         }
         parent[i] = parent[rightmost];
         is_left[i] = false;
-        parent[rightmost] = i;
+        parent[rightmost] = I::from_usize(i);
         is_left[rightmost] = true;
       } else {
-        parent[i] = rightmost;
+        parent[i] = I::from_usize(rightmost);
         is_left[i] = false;
       }
       rightmost = i;
@@ -258,16 +281,17 @@ pub fn cartesian_tree(keys: &Vec<usize>) -> BinTree {
   let mut rights = vec![None; n];
   for i in 0..n {
     if i != root {
+      let kid = Some(I::from_usize(i));
       if is_left[i] {
-        lefts[parent[i]] = Some(i);
+        lefts[parent[i].to_usize()] = kid;
       } else {
-        rights[parent[i]] = Some(i);
+        rights[parent[i].to_usize()] = kid;
       }
     }
   }
   BinTree {
-    root: root,
-    lefts: lefts,
-    rights: rights,
+    root,
+    lefts,
+    rights,
   }
 }
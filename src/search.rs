@@ -0,0 +1,102 @@
+// Pattern search over the suffix array: the binary search of Manber and Myers,
+// which skips the symbols already known to match with lcp queries.
+// The structure doesn't keep the sequence, so it is passed along with the pattern.
+
+use std::cmp::Ordering;
+use std::ops::Range;
+
+use crate::index::IndexType;
+use crate::rmq::{RangeMinQuery, RangeMinView};
+use crate::{SARMQView, SARMQ};
+
+impl<'a, I: IndexType, R: RangeMinView<I>> SARMQView<'a, I, R> {
+
+  /// Ranks of the suffixes starting with pattern, that is the range of `sa` holding its occurrences.
+  /// text is the indexed sequence, with symbols ordered as the ones the structure was created from,
+  /// e.g. the bytes passed to `create_sarmq_from_bytes`.
+  /// O(m + log n) time for a pattern of length m.
+  pub fn sa_range<T: Ord>(&self, text: &[T], pattern: &[T]) -> Range<usize> {
+    assert_eq!(text.len(), self.len(), "Text of a different length than the indexed one.");
+    self.bound(text, pattern, true) .. self.bound(text, pattern, false)
+  }
+
+  /// Number of occurrences of pattern in text, see `sa_range`.
+  pub fn count<T: Ord>(&self, text: &[T], pattern: &[T]) -> usize {
+    self.sa_range(text, pattern).len()
+  }
+
+  /// Starting indices of the occurrences of pattern in text, in the suffix array order, see `sa_range`.
+  pub fn locate<T: Ord>(&self, text: &[T], pattern: &[T]) -> Vec<usize> {
+    self.sa[self.sa_range(text, pattern)].iter().map(|x| x.to_usize()).collect()
+  }
+
+  /// First rank of a suffix that goes after pattern, where suffixes starting with pattern go after it if `matched_right`.
+  fn bound<T: Ord>(&self, text: &[T], pattern: &[T], matched_right: bool) -> usize {
+    let (n, m) = (self.len(), pattern.len());
+    // compares the suffix of the given rank with pattern, knowing that they agree on the first k symbols,
+    // returns whether the suffix goes right, and their lcp
+    let compare = |rank: usize, mut k: usize| {
+      let start = self.sa[rank].to_usize();
+      while k < m && start + k < n && text[start + k] == pattern[k] {
+        k += 1;
+      }
+      let right = if k == m { matched_right } else { start + k < n && text[start + k] > pattern[k] };
+      (right, k)
+    };
+    let lcp = |a: usize, b: usize| self.query(self.sa[a].to_usize(), self.sa[b].to_usize()).min(m);
+    // ranks below lo go left, ranks from hi go right;
+    // l is the lcp of pattern with the suffix of rank lo-1, r with the one of rank hi
+    let (mut lo, mut hi, mut l, mut r) = (0, n, 0, 0);
+    while lo < hi {
+      let mid = lo + (hi - lo) / 2;
+      // only the symbols past the larger of l and r are compared, so the search is O(m + log n)
+      let (right, k) = if l >= r && l > 0 {
+        let h = lcp(lo - 1, mid);
+        match h.cmp(&l) {
+          // mid agrees with lo-1 past where it differs from pattern
+          Ordering::Greater => (false, l),
+          // mid goes after lo-1, so it is larger where they differ
+          Ordering::Less => (true, h),
+          Ordering::Equal => compare(mid, l),
+        }
+      } else if r > l {
+        let h = lcp(mid, hi);
+        match h.cmp(&r) {
+          Ordering::Greater => (true, r),
+          Ordering::Less => (false, h),
+          Ordering::Equal => compare(mid, r),
+        }
+      } else {
+        compare(mid, 0)
+      };
+      if right {
+        hi = mid;
+        r = k;
+      } else {
+        lo = mid + 1;
+        l = k;
+      }
+    }
+    lo
+  }
+
+}
+
+impl<I: IndexType, R: RangeMinQuery<I>> SARMQ<I, R> {
+
+  /// Ranks of the suffixes starting with pattern, see `SARMQView::sa_range`.
+  pub fn sa_range<T: Ord>(&self, text: &[T], pattern: &[T]) -> Range<usize> {
+    self.view().sa_range(text, pattern)
+  }
+
+  /// Number of occurrences of pattern in text, see `SARMQView::sa_range`.
+  pub fn count<T: Ord>(&self, text: &[T], pattern: &[T]) -> usize {
+    self.view().count(text, pattern)
+  }
+
+  /// Starting indices of the occurrences of pattern in text, in the suffix array order, see `SARMQView::sa_range`.
+  pub fn locate<T: Ord>(&self, text: &[T], pattern: &[T]) -> Vec<usize> {
+    self.view().locate(text, pattern)
+  }
+
+}
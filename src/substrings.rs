@@ -0,0 +1,48 @@
+// Counts of distinct substrings, read off the lcp array: every suffix adds its prefixes
+// which are longer than its lcp with the previous suffix in the sorted order.
+
+use crate::index::IndexType;
+use crate::rmq::{RangeMinQuery, RangeMinView};
+use crate::{SARMQView, SARMQ};
+
+impl<'a, I: IndexType, R: RangeMinView<I>> SARMQView<'a, I, R> {
+
+  /// Number of distinct non-empty substrings, n(n+1)/2 - sum(lcp).
+  /// O(n) time.
+  pub fn distinct_substrings(&self) -> usize {
+    let n = self.len();
+    // halved before multiplying, so that only the result has to fit
+    let all = if n.is_multiple_of(2) { n / 2 * (n + 1) } else { n * n.div_ceil(2) };
+    all - self.lcp.iter().map(|x| x.to_usize()).sum::<usize>()
+  }
+
+  /// profile[k] is the number of distinct substrings of length k, for k in 0..=n, with profile[0] = 0,
+  /// so that the profile sums up to `distinct_substrings`.
+  /// O(n) time.
+  pub fn distinct_kmers_per_length(&self) -> Vec<usize> {
+    let n = self.len();
+    // k-mers of the suffixes long enough, except for the ones repeating the k-mer of the previous suffix,
+    // that is n - k + 1 minus the number of lcp values at least k
+    let mut at_least = vec![0; n + 2];
+    self.lcp.iter().for_each(|x| at_least[x.to_usize()] += 1);
+    for k in (0..=n).rev() {
+      at_least[k] += at_least[k + 1];
+    }
+    (0..=n).map(|k| if k == 0 { 0 } else { n - k + 1 - at_least[k] }).collect()
+  }
+
+}
+
+impl<I: IndexType, R: RangeMinQuery<I>> SARMQ<I, R> {
+
+  /// Number of distinct non-empty substrings, see `SARMQView::distinct_substrings`.
+  pub fn distinct_substrings(&self) -> usize {
+    self.view().distinct_substrings()
+  }
+
+  /// Numbers of distinct substrings of each length, see `SARMQView::distinct_kmers_per_length`.
+  pub fn distinct_kmers_per_length(&self) -> Vec<usize> {
+    self.view().distinct_kmers_per_length()
+  }
+
+}
@@ -0,0 +1,216 @@
+// Range minimum queries in 2n + o(n) bits, without the values: the Cartesian tree as balanced parentheses.
+// The parentheses are the pushes and pops of the stack building the tree left to right,
+// and the minimum of a range is found as the lowest point of their excess between its ends.
+
+use crate::bits::{BitsBuilder, RankBits};
+
+// bits per block with a stored minimum excess
+const BLOCK_BITS: usize = 512;
+// blocks per superblock, the minima of which are covered by a sparse table
+const SUPER_BLOCKS: usize = 32;
+
+// minimum prefix excess of a byte from -8, its rightmost position and the excess of the whole byte
+const BYTE_MIN: [(i8, u8, i8); 256] = byte_min();
+
+const fn byte_min() -> [(i8, u8, i8); 256] {
+  let mut table = [(0, 0, 0); 256];
+  let mut byte = 0;
+  while byte < 256 {
+    let (mut excess, mut min, mut pos) = (0, i8::MAX, 0);
+    let mut k = 0;
+    while k < 8 {
+      excess += if byte >> k & 1 == 1 { 1 } else { -1 };
+      if excess <= min {
+        min = excess;
+        pos = k;
+      }
+      k += 1;
+    }
+    table[byte] = (min, pos as u8, excess);
+    byte += 1;
+  }
+  table
+}
+
+/// Answers the position of the leftmost minimum in any range of a sequence, keeping 2n + 2 bits
+/// of the shape of its Cartesian tree and indices over them, but not the values.
+/// The indices take under n/4 bits for rank and select over the parentheses, an i16 minimum per 512 bits (n/16 bits)
+/// and a sparse table of u32s over the minima of every 16384 bits, at most n/8 bits, e.g. 2.3n bits in all for n = 2^30.
+/// The same queries as `RMQ`, for any values, in bits instead of O(n) words, and slower.
+/// O(n) creation time, O(1) query time.
+pub struct SuccinctRMQ {
+  n: usize,
+  // one for pushing an index on the stack, zero for popping one, enclosed in a push and pop of a root
+  bp: RankBits,
+  // minimum excess in each block, relative to the excess before its superblock
+  block_mins: Vec<i16>,
+  // superblock with the least minimum among 2^k from each, the rightmost one of equal ones, by k
+  sparse: Vec<Vec<u32>>,
+}
+
+impl SuccinctRMQ {
+
+  /// Builds the structure over values of any order, which it doesn't keep.
+  /// Consumes them one by one, holding only the increasing ones from the last one back.
+  pub fn create_succinct_rmq<T: Ord>(values: impl IntoIterator<Item = T>) -> Self {
+    // values stay on the stack while no smaller one comes, so equal ones stay as well
+    let mut stack: Vec<T> = vec![];
+    let mut bits = BitsBuilder::default();
+    bits.push(true);
+    let mut n = 0;
+    for x in values {
+      while stack.last().is_some_and(|top| *top > x) {
+        stack.pop();
+        bits.push(false);
+      }
+      stack.push(x);
+      bits.push(true);
+      n += 1;
+    }
+    (0..=stack.len()).for_each(|_| bits.push(false));
+    let bp = bits.build();
+
+    let blocks = bp.len().div_ceil(BLOCK_BITS);
+    let mut rmq = SuccinctRMQ { n, bp, block_mins: Vec::with_capacity(blocks), sparse: vec![] };
+    for b in 0..blocks {
+      let end = ((b + 1) * BLOCK_BITS).min(rmq.bp.len());
+      let min = rmq.scan(b * BLOCK_BITS, end).0 as isize - rmq.super_excess(b / SUPER_BLOCKS);
+      rmq.block_mins.push(min as i16);
+    }
+    // less than 2^32 superblocks for any sequence in memory
+    let supers: Vec<usize> = (0..blocks.div_ceil(SUPER_BLOCKS)).map(|s| rmq.super_min(s).0).collect();
+    let mut level: Vec<u32> = (0..supers.len() as u32).collect();
+    let mut k = 0;
+    while !level.is_empty() {
+      let next = (0..level.len().saturating_sub(1 << k)).map(|j| {
+        let (left, right) = (level[j], level[j + (1 << k)]);
+        if supers[left as usize] < supers[right as usize] { left } else { right }
+      }).collect();
+      rmq.sparse.push(level);
+      level = next;
+      k += 1;
+    }
+    rmq
+  }
+
+  /// Index of the leftmost minimum of `values[left..=right]`.
+  pub fn query(&self, left: usize, right: usize) -> usize {
+    assert!(left <= right && right < self.n, "Query range {left}..={right} out of 0..{}.", self.n);
+    if left == right {
+      return left;
+    }
+    let (x, y) = (self.bp.select1(left + 1), self.bp.select1(right + 1));
+    let (min, pos) = self.min_excess(x, y + 1);
+    if min >= self.excess(x) {
+      // nothing after left went below it on the stack
+      left
+    } else {
+      // the minimum is pushed right after the lowest point
+      self.bp.rank1(pos + 2) - 2
+    }
+  }
+
+  /// Length of the sequence.
+  pub fn len(&self) -> usize {
+    self.n
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.n == 0
+  }
+
+  // pushes minus pops up to and including position p
+  fn excess(&self, p: usize) -> usize {
+    2 * self.bp.rank1(p + 1) - (p + 1)
+  }
+
+  // excess before the superblock
+  fn super_excess(&self, s: usize) -> isize {
+    let p = s * SUPER_BLOCKS * BLOCK_BITS;
+    (2 * self.bp.rank1(p)) as isize - p as isize
+  }
+
+  /// Least excess in from..to and its rightmost position, through the blocks for the middle part.
+  fn min_excess(&self, from: usize, to: usize) -> (usize, usize) {
+    let (first, last) = (from / BLOCK_BITS, (to - 1) / BLOCK_BITS);
+    if first == last {
+      return self.scan(from, to);
+    }
+    let mut best = self.scan(from, (first + 1) * BLOCK_BITS);
+    if first + 1 < last {
+      let (min, block) = self.min_block(first + 1, last);
+      if min <= best.0 {
+        best = self.scan(block * BLOCK_BITS, (block + 1) * BLOCK_BITS);
+      }
+    }
+    let tail = self.scan(last * BLOCK_BITS, to);
+    if tail.0 <= best.0 { tail } else { best }
+  }
+
+  /// Least block minimum among blocks from..to and its rightmost block,
+  /// through the sparse table for the superblocks within, and the blocks around them one by one.
+  fn min_block(&self, from: usize, to: usize) -> (usize, usize) {
+    let (first, last) = (from.div_ceil(SUPER_BLOCKS), to / SUPER_BLOCKS);
+    if first >= last {
+      return self.min_among_blocks(from, to);
+    }
+    let mut best = self.min_among_blocks(from, first * SUPER_BLOCKS);
+    let k = (last - first).ilog2() as usize;
+    for s in [self.sparse[k][first], self.sparse[k][last - (1 << k)]] {
+      let min = self.super_min(s as usize);
+      if min.0 <= best.0 {
+        best = min;
+      }
+    }
+    let tail = self.min_among_blocks(last * SUPER_BLOCKS, to);
+    if tail.0 <= best.0 { tail } else { best }
+  }
+
+  /// Least block minimum of the superblock and its rightmost block.
+  fn super_min(&self, s: usize) -> (usize, usize) {
+    self.min_among_blocks(s * SUPER_BLOCKS, ((s + 1) * SUPER_BLOCKS).min(self.block_mins.len()))
+  }
+
+  /// Least block minimum among blocks from..to, the max value if none, and its rightmost block, one by one.
+  fn min_among_blocks(&self, from: usize, to: usize) -> (usize, usize) {
+    let (mut best, mut base) = ((usize::MAX, from), 0);
+    for b in from..to {
+      if b == from || b.is_multiple_of(SUPER_BLOCKS) {
+        base = self.super_excess(b / SUPER_BLOCKS);
+      }
+      let min = (base + self.block_mins[b] as isize) as usize;
+      if min <= best.0 {
+        best = (min, b);
+      }
+    }
+    best
+  }
+
+  /// Least excess in from..to and its rightmost position, a byte at a time where possible.
+  fn scan(&self, from: usize, to: usize) -> (usize, usize) {
+    let words = self.bp.words();
+    let bit = |p: usize| words[p / 64] >> (p % 64) & 1 == 1;
+    let mut excess = (2 * self.bp.rank1(from)) as isize - from as isize;
+    let (mut min, mut pos) = (isize::MAX, from);
+    let mut p = from;
+    while p < to {
+      if p.is_multiple_of(8) && p + 8 <= to {
+        let (byte_min, byte_pos, byte_excess) = BYTE_MIN[(words[p / 64] >> (p % 64) & 0xff) as usize];
+        if excess + byte_min as isize <= min {
+          min = excess + byte_min as isize;
+          pos = p + byte_pos as usize;
+        }
+        excess += byte_excess as isize;
+        p += 8;
+      } else {
+        excess += if bit(p) { 1 } else { -1 };
+        if excess <= min {
+          (min, pos) = (excess, p);
+        }
+        p += 1;
+      }
+    }
+    (min as usize, pos)
+  }
+
+}
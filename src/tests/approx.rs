@@ -0,0 +1,97 @@
+use crate::approx::{k_difference_alignments, k_difference_occurrences, k_mismatch_occurrences, Alignment, Occurrence};
+use rand::Rng;
+
+#[test]
+pub fn test_k_mismatch_random() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..200 {
+    let text: Vec<u8> = (0..rng.gen_range(0..100)).map(|_| rng.gen_range(b'a'..b'd')).collect();
+    let pattern: Vec<u8> = (0..rng.gen_range(0..10)).map(|_| rng.gen_range(b'a'..b'd')).collect();
+    let k = rng.gen_range(0..4);
+    let expected: Vec<Occurrence> = (0..text.len() + 1).filter(|&p| p + pattern.len() <= text.len())
+      .map(|position| Occurrence {
+        position,
+        mismatches: (0..pattern.len()).filter(|&x| text[position + x] != pattern[x]).collect(),
+      })
+      .filter(|o| o.mismatches.len() <= k)
+      .collect();
+    assert_eq!(k_mismatch_occurrences(&text, &pattern, k), expected);
+  }
+}
+
+#[test]
+pub fn test_k_mismatch_example() {
+  let found = k_mismatch_occurrences(b"abracadabra", b"abda", 1);
+  assert_eq!(found, vec![
+    Occurrence { position: 0, mismatches: vec![2] },
+    Occurrence { position: 7, mismatches: vec![2] },
+  ]);
+  assert!(k_mismatch_occurrences(b"ab", b"abc", 3).is_empty());
+}
+
+// least edit distance of pattern to a substring of text ending at each index
+fn naive_end_distances(text: &[u8], pattern: &[u8]) -> Vec<usize> {
+  let mut column: Vec<usize> = (0..=pattern.len()).collect();
+  let mut ends = vec![pattern.len()];
+  for &t in text {
+    let mut next = vec![0; pattern.len() + 1];
+    for i in 1..=pattern.len() {
+      next[i] = (column[i - 1] + (pattern[i - 1] != t) as usize).min(column[i] + 1).min(next[i - 1] + 1);
+    }
+    ends.push(next[pattern.len()]);
+    column = next;
+  }
+  ends
+}
+
+// edits of the alignment, checking that it transforms text[start..end] into pattern
+fn alignment_cost(text: &[u8], pattern: &[u8], start: usize, end: usize, cigar: &str) -> usize {
+  let (mut t, mut p, mut cost) = (start, 0, 0);
+  let mut run = 0;
+  for c in cigar.chars() {
+    if let Some(digit) = c.to_digit(10) {
+      run = run * 10 + digit as usize;
+      continue;
+    }
+    for _ in 0..run {
+      match c {
+        '=' => { assert_eq!(text[t], pattern[p]); t += 1; p += 1 }
+        'X' => { assert_ne!(text[t], pattern[p]); t += 1; p += 1; cost += 1 }
+        'I' => { p += 1; cost += 1 }
+        'D' => { t += 1; cost += 1 }
+        _ => panic!("unexpected operation {c}"),
+      }
+    }
+    run = 0;
+  }
+  assert_eq!((t, p), (end, pattern.len()), "{cigar}");
+  cost
+}
+
+#[test]
+pub fn test_k_difference_random() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..300 {
+    let text: Vec<u8> = (0..rng.gen_range(0..60)).map(|_| rng.gen_range(b'a'..b'd')).collect();
+    let pattern: Vec<u8> = (0..rng.gen_range(0..8)).map(|_| rng.gen_range(b'a'..b'd')).collect();
+    let k = rng.gen_range(0..4);
+    let expected: Vec<(usize, usize)> = naive_end_distances(&text, &pattern).into_iter().enumerate()
+      .filter(|&(_, distance)| distance <= k)
+      .collect();
+    let found = k_difference_occurrences(&text, &pattern, k);
+    assert_eq!(found.iter().map(|o| (o.end, o.distance)).collect::<Vec<_>>(), expected, "{text:?} {pattern:?} {k}");
+    for o in k_difference_alignments(&text, &pattern, k) {
+      let alignment = o.alignment.unwrap();
+      assert_eq!(alignment_cost(&text, &pattern, alignment.start, o.end, &alignment.cigar), o.distance);
+    }
+  }
+}
+
+#[test]
+pub fn test_k_difference_example() {
+  let found = k_difference_alignments(b"xxabcdefyy", b"abxdef", 1);
+  assert_eq!(found.len(), 1);
+  assert_eq!(found[0].end, 8);
+  assert_eq!(found[0].distance, 1);
+  assert_eq!(found[0].alignment, Some(Alignment { start: 2, cigar: "2=1X3=".to_string() }));
+}
@@ -0,0 +1,40 @@
+use crate::bwt::{bwt, bwt_from_sa, inverse_bwt, Bwt};
+use crate::create_sarmq;
+use rand::Rng;
+
+// last symbols of the sorted rotations of xs with the sentinel, as None
+fn naive_bwt(xs: &[usize]) -> Vec<Option<usize>> {
+  let s: Vec<Option<usize>> = xs.iter().map(|&x| Some(x)).chain([None]).collect();
+  let mut rotations: Vec<Vec<Option<usize>>> = (0..s.len()).map(|i| [&s[i..], &s[..i]].concat()).collect();
+  rotations.sort();
+  rotations.iter().map(|r| *r.last().unwrap()).collect()
+}
+
+#[test]
+pub fn test_bwt_random() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..200 {
+    let n = rng.gen_range(0..50);
+    let xs: Vec<usize> = (0..n).map(|_| rng.gen_range(0..3) * 10).collect();
+    let transform = bwt(&xs);
+    let mut with_sentinel: Vec<Option<usize>> = transform.symbols.iter().map(|&x| Some(x)).collect();
+    with_sentinel.insert(transform.primary_index, None);
+    assert_eq!(with_sentinel, naive_bwt(&xs), "{xs:?}");
+    assert_eq!(inverse_bwt(&transform.symbols, transform.primary_index), xs);
+    if xs.iter().all(|&x| x < n) {
+      assert_eq!(bwt_from_sa(&xs, create_sarmq(&xs).sa()), transform);
+    }
+  }
+}
+
+#[test]
+pub fn test_bwt_example() {
+  let transform = bwt(b"banana");
+  // rotations: $banana, a$banan, ana$ban, anana$b, banana$, na$bana, nana$ba
+  assert_eq!(transform, Bwt { symbols: b"annbaa".to_vec(), primary_index: 4 });
+  assert_eq!(inverse_bwt(b"annbaa", 4), b"banana");
+  let chars: Vec<char> = "mississippi".chars().collect();
+  let transform = bwt(&chars);
+  assert_eq!(inverse_bwt(&transform.symbols, transform.primary_index), chars);
+  assert_eq!(bwt::<u8>(&[]), Bwt { symbols: vec![], primary_index: 0 });
+}
@@ -0,0 +1,39 @@
+use crate::compressed::CompressedSARMQ;
+use crate::{create_sarmq, SarmqError};
+use rand::Rng;
+
+#[test]
+pub fn test_compressed_sarmq_random() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..50 {
+    let n = rng.gen_range(0..1500);
+    let sigma = rng.gen_range(1..4).min(n);
+    let xs: Vec<usize> = (0..n).map(|_| rng.gen_range(0..sigma)).collect();
+    let sarmq = create_sarmq(&xs);
+    let compressed = CompressedSARMQ::create_compressed_sarmq(&xs, rng.gen_range(1..40));
+    // the same order with gaps in the alphabet
+    let spread: Vec<u32> = xs.iter().map(|&x| (x * (n / sigma.max(1))) as u32).collect();
+    let spread = CompressedSARMQ::create_compressed_sarmq(&spread, rng.gen_range(1..40));
+    assert_eq!(compressed.len(), n);
+    for rank in 0..n {
+      assert_eq!(compressed.sa(rank), sarmq.sa()[rank]);
+      assert_eq!(compressed.sa_inverse(rank), sarmq.sa_inverse()[rank]);
+      assert_eq!(compressed.lcp(rank), sarmq.lcp()[rank]);
+    }
+    for _ in 0..200 {
+      let (i, j) = (rng.gen_range(0..=n), rng.gen_range(0..=n));
+      assert_eq!(compressed.query(i, j), sarmq.query(i, j), "{xs:?} ({i}, {j})");
+      assert_eq!(spread.query(i, j), sarmq.query(i, j), "{xs:?} ({i}, {j})");
+    }
+  }
+}
+
+#[test]
+pub fn test_compressed_sarmq_errors() {
+  let compressed = CompressedSARMQ::create_compressed_sarmq(&[1u32, 0, 2, 0, 2, 0], 4);
+  assert_eq!(compressed.query(1, 3), 3);
+  assert_eq!(compressed.query(6, 6), 0);
+  assert_eq!(compressed.checked_query(7, 0), None);
+  assert_eq!(compressed.try_query(0, 7), Err(SarmqError::IndexOutOfRange { index: 7, len: 6 }));
+  assert!(CompressedSARMQ::try_create_compressed_sarmq(&[0usize, 3, 1], 4).is_err());
+}
@@ -0,0 +1,48 @@
+use crate::fm_index::FmIndex;
+use rand::Rng;
+
+#[test]
+pub fn test_fm_index_random() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..100 {
+    let n = rng.gen_range(0..200);
+    let sigma = rng.gen_range(1..6);
+    let xs: Vec<u32> = (0..n).map(|_| rng.gen_range(0..sigma) * 7).collect();
+    let fm = FmIndex::create_fm_index(&xs, rng.gen_range(1..10));
+    assert_eq!(fm.len(), n);
+    for _ in 0..30 {
+      let pattern: Vec<u32> = if n > 0 && rng.gen_bool(0.7) {
+        let i = rng.gen_range(0..n);
+        xs[i..n.min(i + rng.gen_range(0..6))].to_vec()
+      } else {
+        (0..rng.gen_range(0..4)).map(|_| rng.gen_range(0..sigma + 1) * 7).collect()
+      };
+      let expected: Vec<usize> = (0..n).filter(|&i| xs[i..].starts_with(&pattern)).collect();
+      assert_eq!(fm.count(&pattern), expected.len(), "{xs:?} {pattern:?}");
+      let mut found = fm.locate(&pattern);
+      found.sort_unstable();
+      assert_eq!(found, expected);
+
+      let i = rng.gen_range(0..=n);
+      let j = rng.gen_range(i..=n);
+      assert_eq!(fm.extract(i..j), xs[i..j]);
+    }
+  }
+}
+
+#[test]
+pub fn test_fm_index_example() {
+  let text = b"mississippi";
+  for rate in [1, 2, 4, 32] {
+    let fm = FmIndex::create_fm_index(text, rate);
+    assert_eq!(fm.count(b"ssi"), 2);
+    assert_eq!(fm.count(b"i"), 4);
+    assert_eq!(fm.count(b"x"), 0);
+    assert_eq!(fm.count(b"ississippi"), 1);
+    let mut found = fm.locate(b"issi");
+    found.sort_unstable();
+    assert_eq!(found, vec![1, 4]);
+    assert_eq!(fm.extract(0..11), text);
+    assert_eq!(fm.extract(3..7), b"siss");
+  }
+}
@@ -0,0 +1,80 @@
+use crate::generalized::GeneralizedSARMQ;
+use rand::Rng;
+
+#[test]
+pub fn test_generalized_random() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..100 {
+    let docs: Vec<Vec<usize>> = (0..rng.gen_range(1..5))
+      .map(|_| (0..rng.gen_range(0..50)).map(|_| rng.gen_range(0..2)).collect())
+      .collect();
+    let gen = GeneralizedSARMQ::create_generalized_sarmq(&docs);
+    let narrow = GeneralizedSARMQ::<u32>::try_create_with_index(&docs).unwrap();
+    assert_eq!(gen.docs(), docs.len());
+    for _ in 0..200 {
+      let a = rng.gen_range(0..docs.len());
+      let b = rng.gen_range(0..docs.len());
+      let i = rng.gen_range(0..=docs[a].len());
+      let j = rng.gen_range(0..=docs[b].len());
+      let expected = docs[a][i..].iter().zip(&docs[b][j..]).take_while(|(x, y)| x == y).count();
+      assert_eq!(gen.query((a, i), (b, j)), expected);
+      assert_eq!(narrow.query((a, i), (b, j)), expected);
+      assert_eq!(gen.doc_position(gen.position((a, i))), (a, i));
+    }
+  }
+}
+
+#[test]
+pub fn test_generalized_no_match_across_separators() {
+  // the whole of "ab" matches in both, but mustn't continue into the next doc
+  let gen = GeneralizedSARMQ::create_generalized_sarmq(&["ab", "abab", "b"].map(str::as_bytes));
+  assert_eq!(gen.query((0, 0), (1, 0)), 2);
+  assert_eq!(gen.query((0, 0), (1, 2)), 2);
+  assert_eq!(gen.query((0, 1), (2, 0)), 1);
+  assert_eq!(gen.query((0, 2), (1, 4)), 0);
+  assert_eq!(gen.query((1, 1), (1, 1)), 3);
+  assert_eq!(gen.doc_len(1), 4);
+}
+
+#[test]
+pub fn test_longest_common_substring_random() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..200 {
+    let docs: Vec<Vec<usize>> = (0..rng.gen_range(1..5))
+      .map(|_| (0..rng.gen_range(0..20)).map(|_| rng.gen_range(0..3)).collect())
+      .collect();
+    let gen = GeneralizedSARMQ::create_generalized_sarmq(&docs);
+    let k = rng.gen_range(1..=docs.len());
+    // naive: all substrings of all docs, by the number of docs containing them
+    let contains = |doc: &[usize], s: &[usize]| doc.windows(s.len()).any(|w| w == s);
+    let best = docs.iter()
+      .flat_map(|doc| (0..doc.len()).flat_map(move |i| (i + 1..=doc.len()).map(move |j| &doc[i..j])))
+      .filter(|s| docs.iter().filter(|doc| contains(doc, s)).count() >= k)
+      .map(|s| s.len())
+      .max();
+    match gen.longest_common_substring(k) {
+      None => assert_eq!(best, None, "{docs:?} {k}"),
+      Some(common) => {
+        assert_eq!(Some(common.len), best, "{docs:?} {k}");
+        let (d, i) = common.occurrences[0];
+        let s = &docs[d][i..i + common.len];
+        let expected: Vec<(usize, usize)> = docs.iter().enumerate()
+          .flat_map(|(d, doc)| (0..doc.len()).filter(move |&i| doc[i..].starts_with(s)).map(move |i| (d, i)))
+          .collect();
+        assert_eq!(common.occurrences, expected);
+      }
+    }
+  }
+}
+
+#[test]
+pub fn test_longest_common_substring_example() {
+  let gen = GeneralizedSARMQ::create_generalized_sarmq(&["xabcdy", "zzabcq", "bcd"].map(str::as_bytes));
+  let all = gen.longest_common_substring(3).unwrap();
+  assert_eq!(all.len, 2);
+  assert_eq!(all.occurrences, vec![(0, 2), (1, 3), (2, 0)]);
+  let two = gen.longest_common_substring(2).unwrap();
+  assert_eq!(two.len, 3);
+  assert_eq!(two.occurrences, vec![(0, 1), (1, 2)]);
+  assert_eq!(gen.longest_common_substring(4), None);
+}
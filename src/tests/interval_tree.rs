@@ -0,0 +1,82 @@
+use crate::interval_tree::Child;
+use crate::{create_sarmq, create_sarmq_from_str, try_create_sarmq_with_index};
+use rand::Rng;
+
+#[test]
+pub fn test_lcp_interval_tree_random() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..200 {
+    let n = rng.gen_range(0..60);
+    let sigma = rng.gen_range(1..=3.min(n.max(1)));
+    let xs: Vec<usize> = (0..n).map(|_| rng.gen_range(0..sigma)).collect();
+    let sarmq = create_sarmq(&xs);
+    let (sa, sa_inverse, lcp) = (sarmq.sa(), sarmq.sa_inverse(), sarmq.lcp());
+    let tree = sarmq.lcp_interval_tree();
+
+    // the same intervals as enumerated bottom up, besides the root
+    let mut intervals: Vec<_> = tree.intervals().skip(1).collect();
+    let mut expected: Vec<_> = sarmq.lcp_intervals().collect();
+    intervals.sort_by_key(|x| (x.sa_range.start, x.sa_range.end));
+    expected.sort_by_key(|x| (x.sa_range.start, x.sa_range.end));
+    assert_eq!(intervals, expected, "{xs:?}");
+    assert_eq!(tree.sa_range(tree.root()), 0..n);
+
+    for node in 0..tree.len() {
+      let range = tree.sa_range(node);
+      // children cover the node in order, and go deeper
+      let mut r = range.start;
+      for child in tree.children(node) {
+        match child {
+          Child::Interval(c) => {
+            assert_eq!(tree.sa_range(c).start, r);
+            assert_eq!(tree.parent(c), Some(node));
+            assert_eq!(tree.depth(c), tree.depth(node) + 1);
+            assert!(tree.lcp(c) > tree.lcp(node) || (node == 0 && tree.lcp(c) > 0));
+            r = tree.sa_range(c).end;
+          }
+          Child::Leaf(leaf) => {
+            assert_eq!(leaf, r);
+            assert_eq!(tree.leaf_parent(leaf), node);
+            r += 1;
+          }
+        }
+        if r < range.end {
+          assert_eq!(lcp[r - 1], tree.lcp(node), "{xs:?} {node}");
+        }
+      }
+      assert_eq!(r, range.end);
+
+      match tree.suffix_link(node) {
+        None => assert_eq!(node, tree.root()),
+        Some(link) => {
+          assert_eq!(tree.lcp(link), tree.lcp(node) - 1);
+          // the suffixes without their first symbol, the empty one isn't in the tree
+          let shorter = |rank: usize| sa_inverse.get(sa[rank] + 1).copied();
+          for rank in [range.start, range.end - 1] {
+            assert!(shorter(rank).is_none_or(|r| tree.sa_range(link).contains(&r)));
+          }
+          assert!(tree.lcp(link) == 0 || tree.parent(link).is_none_or(|p| tree.lcp(p) < tree.lcp(link)));
+        }
+      }
+    }
+  }
+}
+
+#[test]
+pub fn test_lcp_interval_tree_example() {
+  // sorted suffixes: a, ana, anana, banana, na, nana
+  let sarmq = create_sarmq_from_str("banana");
+  let tree = sarmq.lcp_interval_tree();
+  let root: Vec<Child> = tree.children(tree.root()).collect();
+  assert_eq!(root, vec![Child::Interval(1), Child::Leaf(3), Child::Interval(3)]);
+  assert_eq!((tree.lcp(1), tree.sa_range(1)), (1, 0..3));
+  assert_eq!((tree.lcp(2), tree.sa_range(2)), (3, 1..3));
+  assert_eq!((tree.lcp(3), tree.sa_range(3)), (2, 4..6));
+  // ana -> na -> a
+  assert_eq!(tree.suffix_link(2), Some(3));
+  assert_eq!(tree.suffix_link(3), Some(1));
+  assert_eq!(tree.suffix_link(1), Some(0));
+  assert_eq!(tree.depth(2), 2);
+  let narrow = try_create_sarmq_with_index::<u32>(&[1usize, 0, 2, 0, 2, 0]).unwrap();
+  assert_eq!(narrow.lcp_interval_tree().suffix_link(2), Some(3));
+}
@@ -0,0 +1,75 @@
+use crate::lca::Lca;
+use crate::SarmqError;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+fn naive_lca(parents: &[Option<usize>], mut u: usize, mut v: usize) -> usize {
+  let depth = |mut x: usize| {
+    let mut d = 0;
+    while let Some(p) = parents[x] {
+      x = p;
+      d += 1;
+    }
+    d
+  };
+  let (mut du, mut dv) = (depth(u), depth(v));
+  while du > dv { u = parents[u].unwrap(); du -= 1 }
+  while dv > du { v = parents[v].unwrap(); dv -= 1 }
+  while u != v {
+    u = parents[u].unwrap();
+    v = parents[v].unwrap();
+  }
+  u
+}
+
+#[test]
+pub fn test_lca_random_trees() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..100 {
+    let n = rng.gen_range(1..200);
+    // random labels, each node attached below an earlier one
+    let mut labels: Vec<usize> = (0..n).collect();
+    labels.shuffle(&mut rng);
+    let mut parents = vec![None; n];
+    for i in 1..n {
+      parents[labels[i]] = Some(labels[rng.gen_range(0..i)]);
+    }
+    let mut adjacency = vec![vec![]; n];
+    for (u, p) in parents.iter().enumerate() {
+      if let Some(p) = *p {
+        adjacency[u].push(p);
+        adjacency[p].push(u);
+      }
+    }
+    let from_parents = Lca::from_parents(&parents).unwrap();
+    let from_adjacency = Lca::from_adjacency(labels[0], &adjacency).unwrap();
+    assert_eq!(from_parents.len(), n);
+    for _ in 0..200 {
+      let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+      let expected = naive_lca(&parents, u, v);
+      assert_eq!(from_parents.lca(u, v), expected);
+      assert_eq!(from_adjacency.lca(u, v), expected);
+    }
+  }
+}
+
+#[test]
+pub fn test_lca_example_and_errors() {
+  //      0
+  //    1   2
+  //   3 4   5
+  let lca = Lca::from_adjacency(0, &[vec![1, 2], vec![3, 4], vec![5], vec![], vec![], vec![]]).unwrap();
+  assert_eq!(lca.lca(3, 4), 1);
+  assert_eq!(lca.lca(3, 5), 0);
+  assert_eq!(lca.lca(4, 1), 1);
+  assert_eq!(lca.distance(3, 5), 4);
+  assert_eq!(lca.depth(5), 2);
+
+  assert!(Lca::from_parents(&[]).unwrap().is_empty());
+  assert!(matches!(Lca::from_parents(&[None, Some(0), None]), Err(SarmqError::NotATree { node: 2 })));
+  assert!(matches!(Lca::from_parents(&[Some(1), Some(0)]), Err(SarmqError::NotATree { node: 0 })));
+  assert!(matches!(Lca::from_parents(&[Some(2), None, None]), Err(SarmqError::NotATree { node: 2 })));
+  assert!(matches!(Lca::from_parents(&[None, Some(2), Some(1)]), Err(SarmqError::NotATree { node: 1 })));
+  assert!(matches!(Lca::from_parents(&[None, Some(5)]), Err(SarmqError::IndexOutOfRange { index: 5, len: 2 })));
+  assert!(matches!(Lca::from_adjacency(0, &[vec![1], vec![2], vec![0]]), Err(SarmqError::NotATree { node: 0 })));
+}
@@ -0,0 +1,35 @@
+use crate::lce::BidirectionalLCE;
+use rand::Rng;
+
+#[test]
+pub fn test_lce_both_directions() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..100 {
+    let n = rng.gen_range(0..200);
+    let xs: Vec<usize> = (0..n).map(|_| rng.gen_range(0..2.min(n))).collect();
+    let lce = BidirectionalLCE::create_bidirectional_lce(&xs);
+    let narrow = BidirectionalLCE::<u32>::try_create_with_index(&xs).unwrap();
+    let symbols: Vec<u32> = xs.iter().map(|&x| x as u32).collect();
+    let from_u32 = BidirectionalLCE::create_bidirectional_lce(&symbols);
+    for _ in 0..100 {
+      let (i, j) = (rng.gen_range(0..=n), rng.gen_range(0..=n));
+      let forward = xs[i..].iter().zip(&xs[j..]).take_while(|(a, b)| a == b).count();
+      let backward = xs[..i].iter().rev().zip(xs[..j].iter().rev()).take_while(|(a, b)| a == b).count();
+      assert_eq!(lce.lce_forward(i, j), forward);
+      assert_eq!(lce.lce_backward(i, j), backward);
+      assert_eq!(narrow.lce_backward(i, j), backward);
+      assert_eq!(from_u32.lce_forward(i, j), forward);
+      assert_eq!(from_u32.lce_backward(i, j), backward);
+    }
+  }
+}
+
+#[test]
+pub fn test_lce_from_symbols() {
+  let lce = BidirectionalLCE::create_bidirectional_lce_from_symbols(&"banana".chars().collect::<Vec<_>>());
+  assert_eq!(lce.lce_forward(1, 3), 3);
+  // "ban" and "banan" end with "an"
+  assert_eq!(lce.lce_backward(3, 5), 2);
+  assert_eq!(lce.lce_backward(6, 6), 6);
+  assert_eq!(lce.lce_backward(0, 4), 0);
+}
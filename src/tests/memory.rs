@@ -0,0 +1,85 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use crate::rmq::SuccinctRMQ;
+use crate::sa::SA;
+use crate::try_create_sarmq_with_index;
+use rand::Rng;
+
+// counts the bytes held by each thread, so that tests running in parallel don't mix up
+struct CountingAlloc;
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+thread_local! {
+  static HELD: Cell<isize> = const { Cell::new(0) };
+  static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+fn track(bytes: isize) {
+  // the thread locals may already be gone while a thread exits
+  let _ = HELD.try_with(|held| {
+    held.set(held.get() + bytes);
+    let _ = PEAK.try_with(|peak| peak.set(peak.get().max(held.get())));
+  });
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    let ptr = unsafe { System.alloc(layout) };
+    if !ptr.is_null() {
+      track(layout.size() as isize);
+    }
+    ptr
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    unsafe { System.dealloc(ptr, layout) };
+    track(-(layout.size() as isize));
+  }
+
+  unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+    if !new_ptr.is_null() {
+      track(new_size as isize - layout.size() as isize);
+    }
+    new_ptr
+  }
+}
+
+// the most bytes held at once while running f, and the bytes still held by its result, above what was held before
+fn peak_bytes<R>(f: impl FnOnce() -> R) -> (usize, usize) {
+  let held = HELD.with(|held| held.get());
+  PEAK.with(|peak| peak.set(held));
+  let result = f();
+  let (peak, kept) = (PEAK.with(|peak| peak.get()), HELD.with(|now| now.get()));
+  drop(result);
+  ((peak - held) as usize, (kept - held) as usize)
+}
+
+#[test]
+pub fn test_construction_memory() {
+  let mut rng = rand::thread_rng();
+  let n = 1 << 18;
+  for alphabet in [2, 4, n] {
+    let xs: Vec<u32> = (0..n).map(|_| rng.gen_range(0..alphabet) as u32).collect();
+    // the suffix array and its inverse take 8 bytes per symbol, the recursion about as much on top
+    let (peak, kept) = peak_bytes(|| SA::<u32>::create_suffix_array(&xs));
+    assert_eq!(kept, 8 * n);
+    assert!(peak - kept < 16 * n, "suffix array: {} bytes per symbol", peak as f64 / n as f64);
+    // temporary arrays of the whole construction stay within a few u32s per symbol beyond the structure
+    let (peak, kept) = peak_bytes(|| try_create_sarmq_with_index::<u32>(&xs).unwrap());
+    assert!(peak - kept < 24 * n, "sarmq: {} bytes per symbol, {} kept", peak as f64 / n as f64, kept as f64 / n as f64);
+  }
+}
+
+#[test]
+pub fn test_succinct_rmq_size() {
+  let mut rng = rand::thread_rng();
+  let n = 1 << 20;
+  let xs: Vec<u32> = (0..n).map(|_| rng.gen_range(0..1000)).collect();
+  // 2n bits of parentheses and the indices over them
+  let (_, kept) = peak_bytes(|| SuccinctRMQ::create_succinct_rmq(&xs));
+  assert!(8 * kept < 5 * n / 2, "{} bits per value", 8.0 * kept as f64 / n as f64);
+}
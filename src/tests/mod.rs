@@ -1,7 +1,24 @@
 // #[cfg(test)]
 // use super::*;
 
-use crate::create_sarmq;
+mod approx;
+mod bwt;
+mod compressed;
+mod fm_index;
+mod generalized;
+mod interval_tree;
+mod lca;
+mod lce;
+mod memory;
+mod persist;
+mod repeats;
+mod rmq;
+mod search;
+mod substrings;
+mod succinct;
+
+use crate::rmq::{SparseTableRMQ, SqrtRMQ, SuccinctRMQ};
+use crate::{create_sarmq, create_sarmq_from_bytes, create_sarmq_from_chars, create_sarmq_from_iter, create_sarmq_from_str, create_sarmq_from_symbols, create_sarmq_with_rmq, try_create_sarmq, try_create_sarmq_with_index, try_create_sarmq_with_rmq, SarmqError};
 use rand::Rng;
 
 // #[test]
@@ -125,4 +142,183 @@ pub fn test_big_n_binary() {
   random_query_random_sequence(10000000, Some(2));
   random_query_random_sequence(10000000, Some(2));
   random_query_random_sequence(100000000, Some(2));
-}
\ No newline at end of file
+}
+#[test]
+pub fn test_exposed_arrays() {
+  let mut rng = rand::thread_rng();
+  for n in [1, 2, 10, 50, 300] {
+    let xs: Vec<usize> = (0..n).map(|_| rng.gen_range(0..3)).collect();
+    let sarmq = create_sarmq(&xs);
+    let mut naive: Vec<usize> = (0..n).collect();
+    naive.sort_by_key(|&i| &xs[i..]);
+    assert_eq!(sarmq.sa(), &naive[..]);
+    for (rank, &i) in sarmq.sa().iter().enumerate() {
+      assert_eq!(sarmq.sa_inverse()[i], rank);
+    }
+    for rank in 0..n-1 {
+      assert_eq!(sarmq.lcp()[rank], sarmq.query(naive[rank], naive[rank+1]));
+    }
+  }
+}
+
+fn naive_lcp<T: Eq>(xs: &[T], i: usize, j: usize) -> usize {
+  xs[i..].iter().zip(&xs[j..]).take_while(|(a, b)| a == b).count()
+}
+
+#[test]
+pub fn test_generic_alphabets() {
+  let text = "mississippi, żółw i mississippi";
+  let bytes = create_sarmq_from_str(text);
+  for i in 0..text.len() {
+    for j in 0..text.len() {
+      assert_eq!(bytes.query(i, j), naive_lcp(text.as_bytes(), i, j));
+    }
+  }
+  let chars: Vec<char> = text.chars().collect();
+  let by_char = create_sarmq_from_chars(text);
+  let symbols = create_sarmq_from_symbols(&chars);
+  for i in 0..chars.len() {
+    for j in 0..chars.len() {
+      assert_eq!(by_char.query(i, j), naive_lcp(&chars, i, j));
+      assert_eq!(symbols.query(i, j), naive_lcp(&chars, i, j));
+    }
+  }
+
+  let mut rng = rand::thread_rng();
+  let xs: Vec<u8> = (0..500).map(|_| rng.gen_range(250..=255)).collect();
+  let sarmq = create_sarmq_from_bytes(&xs);
+  for _ in 0..1000 {
+    let (i, j) = (rng.gen_range(0..xs.len()), rng.gen_range(0..xs.len()));
+    assert_eq!(sarmq.query(i, j), naive_lcp(&xs, i, j));
+  }
+  let words: Vec<i64> = (0..500).map(|_| rng.gen_range(-3..3) * 1_000_000_007).collect();
+  let sarmq = create_sarmq_from_symbols(&words);
+  for _ in 0..1000 {
+    let (i, j) = (rng.gen_range(0..words.len()), rng.gen_range(0..words.len()));
+    assert_eq!(sarmq.query(i, j), naive_lcp(&words, i, j));
+  }
+}
+
+#[test]
+pub fn test_alphabet_validation() {
+  let xs: Vec<usize> = vec![0, 1, 2, 1, 0, 1, 2, 1, 0, 1, 2, 20];
+  assert_eq!(try_create_sarmq(&xs).err(), Some(SarmqError::SymbolOutOfRange { index: 11, symbol: 20, n: 12 }));
+  assert!(crate::sa::SA::<usize>::try_create_suffix_array(&xs).is_err());
+  assert_eq!(try_create_sarmq(&[0u32, 1, 3]).err(), Some(SarmqError::SymbolOutOfRange { index: 2, symbol: 3, n: 3 }));
+  let sarmq = try_create_sarmq(&xs[..11]).unwrap();
+  assert_eq!(sarmq.query(0, 4), 7);
+  let sarmq = create_sarmq_from_symbols(&xs);
+  assert_eq!(sarmq.query(0, 4), 7);
+  assert_eq!(sarmq.query(0, 8), 3);
+}
+
+#[test]
+pub fn test_checked_query() {
+  let xs: Vec<usize> = vec![1, 0, 1, 0, 1];
+  let sarmq = create_sarmq(&xs);
+  let n = xs.len();
+  assert_eq!(sarmq.len(), n);
+  assert_eq!(sarmq.query(0, 2), 3);
+  assert_eq!(sarmq.query(1, 1), 4);
+  // the empty suffix
+  assert_eq!(sarmq.query(n, n), 0);
+  assert_eq!(sarmq.query(2, n), 0);
+  assert_eq!(sarmq.query(n, 0), 0);
+  assert_eq!(sarmq.checked_query(0, 2), Some(3));
+  assert_eq!(sarmq.checked_query(n, 3), Some(0));
+  assert_eq!(sarmq.checked_query(n + 1, 3), None);
+  assert_eq!(sarmq.try_query(3, n + 7), Err(SarmqError::IndexOutOfRange { index: n + 7, len: n }));
+
+  let empty = create_sarmq(&[0usize; 0]);
+  assert!(empty.is_empty());
+  assert_eq!(empty.query(0, 0), 0);
+  assert_eq!(empty.checked_query(0, 1), None);
+}
+
+#[test]
+pub fn test_all_short_binary() {
+  for n in 1..=12usize {
+    for mask in 0..(1usize << n) {
+      let xs: Vec<usize> = (0..n).map(|b| (mask >> b) & 1).collect();
+      let sarmq = create_sarmq(&xs);
+      for i in 0..n {
+        for j in 0..n {
+          assert_eq!(sarmq.query(i, j), naive_lcp(&xs, i, j), "{:?} {} {}", xs, i, j);
+        }
+      }
+    }
+  }
+}
+
+#[test]
+pub fn test_slice_and_iter_input() {
+  let mut rng = rand::thread_rng();
+  let buf: Box<[u32]> = (0..400).map(|_| rng.gen_range(0..4)).collect();
+  let from_iter = create_sarmq_from_iter(buf.iter().map(|&x| x as usize));
+  let from_symbols = create_sarmq_from_symbols(&buf);
+  let from_slice = create_sarmq(&buf[100..300]);
+  let from_u32_iter = create_sarmq_from_iter(buf.iter().copied());
+  for _ in 0..1000 {
+    let (i, j) = (rng.gen_range(0..buf.len()), rng.gen_range(0..buf.len()));
+    assert_eq!(from_iter.query(i, j), naive_lcp(&buf, i, j));
+    assert_eq!(from_symbols.query(i, j), naive_lcp(&buf, i, j));
+    assert_eq!(from_u32_iter.query(i, j), naive_lcp(&buf, i, j));
+    let (i, j) = (rng.gen_range(0..200), rng.gen_range(0..200));
+    assert_eq!(from_slice.query(i, j), naive_lcp(&buf[100..300], i, j));
+  }
+}
+
+#[test]
+pub fn test_u32_index() {
+  let mut rng = rand::thread_rng();
+  let xs: Vec<usize> = (0..3000).map(|_| rng.gen_range(0..3)).collect();
+  let wide = create_sarmq(&xs);
+  let narrow = try_create_sarmq_with_index::<u32>(&xs).unwrap();
+  assert!(narrow.sa().iter().zip(wide.sa()).all(|(&a, &b)| a as usize == b));
+  assert!(narrow.lcp().iter().zip(wide.lcp()).all(|(&a, &b)| a as usize == b));
+  for _ in 0..3000 {
+    let (i, j) = (rng.gen_range(0..=xs.len()), rng.gen_range(0..=xs.len()));
+    assert_eq!(narrow.query(i, j), wide.query(i, j));
+  }
+  assert_eq!(crate::index::check_len(5_000_000_000, u32::MAX as usize / 2),
+    Err(SarmqError::LengthOverflow { len: 5_000_000_000, max_len: u32::MAX as usize / 2 }));
+}
+
+#[test]
+pub fn test_rmq_backends() {
+  let mut rng = rand::thread_rng();
+  for n in [0, 1, 2, 50, 3000] {
+    let xs: Vec<usize> = (0..n).map(|_| rng.gen_range(0..3.min(n.max(1)))).collect();
+    let default = create_sarmq(&xs);
+    let sparse = create_sarmq_with_rmq::<SparseTableRMQ>(&xs);
+    let sqrt = create_sarmq_with_rmq::<SqrtRMQ>(&xs);
+    let succinct = create_sarmq_with_rmq::<SuccinctRMQ>(&xs);
+    let narrow = try_create_sarmq_with_rmq::<u32, SparseTableRMQ<u32>>(&xs).unwrap();
+    for _ in 0..1000 {
+      let (i, j) = (rng.gen_range(0..=n), rng.gen_range(0..=n));
+      let expected = naive_lcp(&xs, i, j);
+      assert_eq!(default.query(i, j), expected);
+      assert_eq!(sparse.query(i, j), expected);
+      assert_eq!(sqrt.query(i, j), expected);
+      assert_eq!(succinct.query(i, j), expected);
+      assert_eq!(narrow.query(i, j), expected);
+    }
+    assert_eq!(succinct.checked_query(n + 1, 0), None);
+    // the structures over the suffix array work with any backend
+    let (tree, succinct_tree) = (default.lcp_interval_tree(), succinct.lcp_interval_tree());
+    assert!((0..tree.len()).all(|node| tree.suffix_link(node) == succinct_tree.suffix_link(node)));
+    let pattern = &xs[..n.min(2)];
+    assert_eq!(sqrt.locate(&xs, pattern), default.locate(&xs, pattern));
+    assert_eq!(sparse.distinct_substrings(), default.distinct_substrings());
+    assert!(sparse.view().maximal_repeats(&xs).map(|r| r.sa_range).eq(default.maximal_repeats(&xs).map(|r| r.sa_range)));
+  }
+  assert!(try_create_sarmq_with_rmq::<usize, SqrtRMQ>(&[0usize, 5]).is_err());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "does not fit in u32")]
+pub fn test_index_overflow() {
+  use crate::IndexType;
+  u32::from_usize(u32::MAX as usize + 1);
+}
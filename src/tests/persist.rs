@@ -0,0 +1,129 @@
+use crate::{create_sarmq, try_create_sarmq_with_index, PersistError, SARMQView, SARMQ};
+use rand::Rng;
+
+fn saved<I: crate::IndexType>(sarmq: &SARMQ<I>) -> Vec<u8> {
+  let mut buf = vec![];
+  sarmq.save(&mut buf).unwrap();
+  buf
+}
+
+#[test]
+pub fn test_save_load_roundtrip() {
+  let mut rng = rand::thread_rng();
+  for n in [0, 1, 2, 10, 1000, 20000] {
+    let xs: Vec<usize> = (0..n).map(|_| rng.gen_range(0..3.min(n))).collect();
+    let sarmq = create_sarmq(&xs);
+    let loaded = SARMQ::<usize>::load(&saved(&sarmq)[..]).unwrap();
+    assert_eq!(loaded.sa(), sarmq.sa());
+    assert_eq!(loaded.lcp(), sarmq.lcp());
+    for _ in 0..1000 {
+      let (i, j) = (rng.gen_range(0..=n), rng.gen_range(0..=n));
+      assert_eq!(loaded.query(i, j), sarmq.query(i, j));
+    }
+    let narrow = try_create_sarmq_with_index::<u32>(&xs).unwrap();
+    let loaded = SARMQ::<u32>::load(&saved(&narrow)[..]).unwrap();
+    for _ in 0..1000 {
+      let (i, j) = (rng.gen_range(0..=n), rng.gen_range(0..=n));
+      assert_eq!(loaded.query(i, j), sarmq.query(i, j));
+    }
+  }
+}
+
+#[test]
+pub fn test_save_load_file() {
+  let xs: Vec<usize> = vec![2, 0, 1, 2, 0, 1, 2, 0];
+  let sarmq = create_sarmq(&xs);
+  let path = std::env::temp_dir().join(format!("sarmq_test_{}.bin", std::process::id()));
+  sarmq.save_to_file(&path).unwrap();
+  let loaded = SARMQ::<usize>::load_from_file(&path);
+  std::fs::remove_file(&path).unwrap();
+  assert_eq!(loaded.unwrap().query(0, 3), 5);
+}
+
+#[test]
+pub fn test_load_rejects_bad_files() {
+  let mut rng = rand::thread_rng();
+  let xs: Vec<usize> = (0..500).map(|_| rng.gen_range(0..4)).collect();
+  let buf = saved(&create_sarmq(&xs));
+
+  for _ in 0..200 {
+    let mut flipped = buf.clone();
+    flipped[rng.gen_range(16..buf.len())] ^= 1 << rng.gen_range(0..8);
+    assert!(matches!(SARMQ::<usize>::load(&flipped[..]), Err(PersistError::ChecksumMismatch | PersistError::Corrupted(_))));
+  }
+
+  assert!(matches!(SARMQ::<usize>::load(&buf[..buf.len() / 2]), Err(PersistError::Io(_))));
+  assert!(matches!(SARMQ::<u32>::load(&buf[..]), Err(PersistError::IndexWidthMismatch { expected: 4, found: 8 })));
+  assert!(matches!(SARMQ::<usize>::load(&b"not a sarmq file"[..]), Err(PersistError::BadMagic)));
+
+  let mut newer = buf.clone();
+  newer[8] = 99;
+  assert!(matches!(SARMQ::<usize>::load(&newer[..]), Err(PersistError::UnsupportedVersion { version: 99 })));
+}
+
+// copies the bytes into an 8 byte aligned buffer, as a memory map would be
+fn aligned(buf: &[u8]) -> Vec<u64> {
+  let mut words = vec![0u64; buf.len().div_ceil(8)];
+  bytes_of_mut(&mut words)[..buf.len()].copy_from_slice(buf);
+  words
+}
+
+fn bytes_of(words: &[u64]) -> &[u8] {
+  unsafe { std::slice::from_raw_parts(words.as_ptr() as *const u8, words.len() * 8) }
+}
+
+fn bytes_of_mut(words: &mut [u64]) -> &mut [u8] {
+  unsafe { std::slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, words.len() * 8) }
+}
+
+#[test]
+pub fn test_view_from_bytes() {
+  let mut rng = rand::thread_rng();
+  for n in [0, 1, 2, 10, 1000, 20000] {
+    let xs: Vec<usize> = (0..n).map(|_| rng.gen_range(0..3.min(n))).collect();
+    let sarmq = try_create_sarmq_with_index::<u32>(&xs).unwrap();
+    let buf = saved(&sarmq);
+    // saved structures are a multiple of 8 bytes long
+    assert_eq!(buf.len() % 8, 0);
+    let words = aligned(&buf);
+    let view = SARMQView::<u32>::from_bytes(bytes_of(&words)).unwrap();
+    let trusted = SARMQView::<u32>::from_bytes_trusted(bytes_of(&words)).unwrap();
+    assert_eq!(view.sa(), sarmq.sa());
+    assert_eq!(view.lcp(), sarmq.lcp());
+    for _ in 0..1000 {
+      let (i, j) = (rng.gen_range(0..=n), rng.gen_range(0..=n));
+      assert_eq!(view.query(i, j), sarmq.query(i, j));
+      assert_eq!(trusted.query(i, j), sarmq.query(i, j));
+    }
+    let mut resaved = vec![];
+    view.save(&mut resaved).unwrap();
+    assert_eq!(resaved, buf);
+  }
+}
+
+#[test]
+pub fn test_view_rejects_bad_buffers() {
+  let mut rng = rand::thread_rng();
+  let xs: Vec<usize> = (0..500).map(|_| rng.gen_range(0..4)).collect();
+  let buf = saved(&create_sarmq(&xs));
+  let mut words = aligned(&buf);
+  // one byte longer, to have room for a misaligned copy
+  words.push(0);
+
+  let misaligned = &bytes_of(&words)[1..buf.len() + 1];
+  assert!(matches!(SARMQView::<usize>::from_bytes(misaligned), Err(PersistError::Unsupported(_))));
+  let bytes = &bytes_of(&words)[..buf.len()];
+  assert!(matches!(SARMQView::<u32>::from_bytes(bytes), Err(PersistError::IndexWidthMismatch { expected: 4, found: 8 })));
+  assert!(matches!(SARMQView::<usize>::from_bytes(&bytes[..buf.len() - 8]), Err(PersistError::ChecksumMismatch)));
+
+  for _ in 0..200 {
+    let mut flipped = words.clone();
+    bytes_of_mut(&mut flipped)[rng.gen_range(16..buf.len())] ^= 1 << rng.gen_range(0..8);
+    let bytes = &bytes_of(&flipped)[..buf.len()];
+    assert!(matches!(SARMQView::<usize>::from_bytes(bytes), Err(PersistError::ChecksumMismatch)));
+    // without the checksum, corruption is only noticed in the lengths
+    if let Err(e) = SARMQView::<usize>::from_bytes_trusted(bytes) {
+      assert!(matches!(e, PersistError::Corrupted(_)));
+    }
+  }
+}
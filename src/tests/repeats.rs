@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+
+use crate::create_sarmq;
+use rand::Rng;
+
+// every substring occurring at least twice, with its sorted occurrences, and whether it extends to the left and to the right
+fn naive_repeats(xs: &[usize]) -> BTreeMap<Vec<usize>, (Vec<usize>, bool, bool)> {
+  let n = xs.len();
+  let mut repeats = BTreeMap::new();
+  for len in 1..n {
+    for i in 0..=n - len {
+      let s = &xs[i..i + len];
+      let occurrences: Vec<usize> = (0..=n - len).filter(|&j| &xs[j..j + len] == s).collect();
+      if occurrences.len() < 2 {
+        continue;
+      }
+      let extends = |at: &dyn Fn(usize) -> Option<usize>| {
+        let first = at(occurrences[0]);
+        first.is_some() && occurrences.iter().all(|&j| at(j) == first)
+      };
+      let left = extends(&|j| j.checked_sub(1).map(|j| xs[j]));
+      let right = extends(&|j| xs.get(j + len).copied());
+      repeats.insert(s.to_vec(), (occurrences, left, right));
+    }
+  }
+  repeats
+}
+
+fn sorted(xs: &[usize]) -> Vec<usize> {
+  let mut xs = xs.to_vec();
+  xs.sort_unstable();
+  xs
+}
+
+#[test]
+pub fn test_repeats_random() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..200 {
+    let n = rng.gen_range(0..40);
+    let sigma = rng.gen_range(1..=3.min(n.max(1)));
+    let xs: Vec<usize> = (0..n).map(|_| rng.gen_range(0..sigma)).collect();
+    let sarmq = create_sarmq(&xs);
+    let naive = naive_repeats(&xs);
+    let substring = |len: usize, occurrences: &[usize]| xs[occurrences[0]..occurrences[0] + len].to_vec();
+
+    let intervals: BTreeMap<Vec<usize>, Vec<usize>> = sarmq.lcp_intervals()
+      .map(|interval| {
+        let occurrences = sorted(&sarmq.sa()[interval.sa_range]);
+        (substring(interval.lcp, &occurrences), occurrences)
+      })
+      .collect();
+    let expected: BTreeMap<Vec<usize>, Vec<usize>> = naive.iter()
+      .filter(|(_, (_, _, right))| ! right)
+      .map(|(s, (occurrences, _, _))| (s.clone(), occurrences.clone()))
+      .collect();
+    assert_eq!(intervals, expected, "{xs:?}");
+
+    let maximal: BTreeMap<Vec<usize>, Vec<usize>> = sarmq.maximal_repeats(&xs)
+      .map(|r| (substring(r.len, r.occurrences), sorted(r.occurrences)))
+      .collect();
+    let expected: BTreeMap<Vec<usize>, Vec<usize>> = naive.iter()
+      .filter(|(_, (_, left, right))| ! left && ! right)
+      .map(|(s, (occurrences, _, _))| (s.clone(), occurrences.clone()))
+      .collect();
+    assert_eq!(maximal, expected, "{xs:?}");
+
+    let contains = |a: &[usize], b: &[usize]| a.len() > b.len() && a.windows(b.len()).any(|w| w == b);
+    let supermaximal: Vec<Vec<usize>> = sarmq.supermaximal_repeats(&xs).map(|r| substring(r.len, r.occurrences)).collect();
+    let expected: Vec<Vec<usize>> = maximal.keys().filter(|s| ! maximal.keys().any(|t| contains(t, s))).cloned().collect();
+    assert_eq!(sorted_vecs(supermaximal), expected, "{xs:?}");
+
+    let longest: Vec<Vec<usize>> = sarmq.longest_repeats().map(|r| substring(r.len, r.occurrences)).collect();
+    let len = naive.keys().map(|s| s.len()).max();
+    let expected: Vec<Vec<usize>> = naive.keys().filter(|s| Some(s.len()) == len).cloned().collect();
+    assert_eq!(sorted_vecs(longest), expected, "{xs:?}");
+  }
+}
+
+fn sorted_vecs(mut xs: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+  xs.sort();
+  xs
+}
+
+#[test]
+pub fn test_repeats_example() {
+  let text = b"abcabxabcd";
+  let sarmq = crate::create_sarmq_from_bytes(text);
+  let longest: Vec<_> = sarmq.longest_repeats().collect();
+  assert_eq!(longest.len(), 1);
+  assert_eq!(longest[0].len, 3);
+  assert_eq!(sorted(longest[0].occurrences), vec![0, 6]);
+  let maximal: Vec<&[u8]> = sarmq.maximal_repeats(text).map(|r| &text[r.occurrences[0]..][..r.len]).collect();
+  // intervals inside come first
+  assert_eq!(maximal, vec![&b"abc"[..], b"ab"]);
+  let supermaximal: Vec<&[u8]> = sarmq.supermaximal_repeats(text).map(|r| &text[r.occurrences[0]..][..r.len]).collect();
+  assert_eq!(supermaximal, vec![&b"abc"[..]]);
+}
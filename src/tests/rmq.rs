@@ -0,0 +1,96 @@
+use crate::rmq::{ByCmp, ByKey, CartesianRMQ, EulerRMQ, Maximum, Minimum, RangeMinQuery, Rightmost, Selector, SparseTableRMQ, SqrtRMQ, SuccinctRMQ};
+use rand::Rng;
+
+fn naive_argmin<T: Ord>(xs: &[T], left: usize, right: usize) -> usize {
+  (left..=right).fold(left, |best, i| if xs[i] < xs[best] { i } else { best })
+}
+
+fn naive_select<T, S: Selector<T>>(xs: &[T], selector: &S, left: usize, right: usize) -> usize {
+  (left..=right).fold(left, |best, i| match selector.cmp(&xs[i], &xs[best]) {
+    std::cmp::Ordering::Less => i,
+    std::cmp::Ordering::Equal if ! selector.leftmost() => i,
+    _ => best,
+  })
+}
+
+fn random_queries<T: Ord + std::fmt::Debug>(xs: &[T], rmq: &CartesianRMQ<T>, queries: usize) {
+  random_selector_queries(xs, rmq, queries);
+  let mut rng = rand::thread_rng();
+  for _ in 0..queries {
+    let left = rng.gen_range(0..xs.len());
+    let right = rng.gen_range(left..xs.len());
+    assert_eq!(rmq.query(left, right).index, naive_argmin(xs, left, right));
+  }
+}
+
+fn random_selector_queries<T: std::fmt::Debug, S: Selector<T>>(xs: &[T], rmq: &CartesianRMQ<T, S>, queries: usize) {
+  let mut rng = rand::thread_rng();
+  for _ in 0..queries {
+    let left = rng.gen_range(0..xs.len());
+    let right = rng.gen_range(left..xs.len());
+    let selected = rmq.query(left, right);
+    assert_eq!(selected.index, naive_select(xs, rmq.selector(), left, right), "{:?} [{}, {}]", xs, left, right);
+    assert!(std::ptr::eq(selected.value, &rmq.values()[selected.index]));
+  }
+}
+
+#[test]
+pub fn test_cartesian_rmq_arbitrary_values() {
+  let mut rng = rand::thread_rng();
+  for n in [1, 2, 3, 10, 100, 1000, 100000] {
+    let xs: Vec<i64> = (0..n).map(|_| rng.gen_range(-1_000_000_000..1_000_000_000)).collect();
+    random_queries(&xs, &CartesianRMQ::create_cartesian_rmq(&xs[..]), 1000);
+    // many ties, leftmost argmin expected
+    let xs: Vec<u8> = (0..n).map(|_| rng.gen_range(0..3)).collect();
+    random_queries(&xs, &CartesianRMQ::create_cartesian_rmq(xs.clone()), 1000);
+  }
+  let words = ["pear", "apple", "fig", "apple", "kiwi"].map(String::from);
+  let rmq = CartesianRMQ::create_cartesian_rmq(&words[..]);
+  assert_eq!(rmq.query(0, 4).index, 1);
+  assert_eq!(rmq.query(2, 4).value, "apple");
+  assert_eq!(rmq.query(4, 4).index, 4);
+}
+
+#[test]
+pub fn test_cartesian_rmq_selectors() {
+  let mut rng = rand::thread_rng();
+  for n in [1, 2, 5, 50, 1000, 20000] {
+    let xs: Vec<u8> = (0..n).map(|_| rng.gen_range(0..4)).collect();
+    random_selector_queries(&xs, &CartesianRMQ::create_with_selector(&xs[..], Minimum), 500);
+    random_selector_queries(&xs, &CartesianRMQ::create_with_selector(&xs[..], Maximum), 500);
+    random_selector_queries(&xs, &CartesianRMQ::create_with_selector(&xs[..], Rightmost(Minimum)), 500);
+    random_selector_queries(&xs, &CartesianRMQ::create_with_selector(&xs[..], Rightmost(Maximum)), 500);
+    let pairs: Vec<(u8, i32)> = (0..n).map(|_| (rng.gen_range(0..4), rng.gen_range(-5..5))).collect();
+    random_selector_queries(&pairs, &CartesianRMQ::create_with_selector(&pairs[..], ByKey(|p: &(u8, i32)| p.1)), 500);
+    random_selector_queries(&pairs, &CartesianRMQ::create_with_selector(&pairs[..],
+      Rightmost(ByCmp(|a: &(u8, i32), b: &(u8, i32)| b.1.abs().cmp(&a.1.abs())))), 500);
+  }
+  let xs = [3, 1, 4, 1, 5, 9, 2, 6, 5, 3, 5];
+  assert_eq!(CartesianRMQ::create_with_selector(&xs[..], Maximum).query(0, 10).index, 5);
+  assert_eq!(CartesianRMQ::create_with_selector(&xs[..], Rightmost(Minimum)).query(0, 10).index, 3);
+  assert_eq!(CartesianRMQ::create_with_selector(&xs[..], Rightmost(Maximum)).query(8, 10).index, 10);
+}
+
+fn random_backend_queries<R: RangeMinQuery<u32>>(xs: &[u32]) {
+  let rmq = R::build(xs);
+  let mut rng = rand::thread_rng();
+  for _ in 0..500 {
+    let left = rng.gen_range(0..xs.len());
+    let right = rng.gen_range(left..xs.len());
+    assert_eq!(rmq.query(xs, left, right), naive_argmin(xs, left, right), "{xs:?} [{left}, {right}]");
+  }
+}
+
+#[test]
+pub fn test_range_min_query_backends() {
+  let mut rng = rand::thread_rng();
+  for n in [1, 2, 3, 10, 100, 1000, 20000] {
+    for range in [3, 1_000_000] {
+      let xs: Vec<u32> = (0..n).map(|_| rng.gen_range(0..range)).collect();
+      random_backend_queries::<EulerRMQ<u32>>(&xs);
+      random_backend_queries::<SparseTableRMQ<u32>>(&xs);
+      random_backend_queries::<SqrtRMQ<u32>>(&xs);
+      random_backend_queries::<SuccinctRMQ>(&xs);
+    }
+  }
+}
@@ -0,0 +1,53 @@
+use crate::{create_sarmq, create_sarmq_from_bytes, try_create_sarmq_with_index};
+use rand::Rng;
+
+fn naive_locate<T: Eq>(text: &[T], pattern: &[T]) -> Vec<usize> {
+  (0..text.len()).filter(|&i| text[i..].starts_with(pattern)).collect()
+}
+
+#[test]
+pub fn test_count_locate_random() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..200 {
+    let n = rng.gen_range(0..300);
+    let sigma = rng.gen_range(1..=4.min(n.max(1)));
+    let xs: Vec<usize> = (0..n).map(|_| rng.gen_range(0..sigma)).collect();
+    let sarmq = create_sarmq(&xs);
+    let narrow = try_create_sarmq_with_index::<u32>(&xs).unwrap();
+    for _ in 0..50 {
+      // substrings of the text, and random patterns which mostly don't occur
+      let pattern: Vec<usize> = if n > 0 && rng.gen_bool(0.5) {
+        let i = rng.gen_range(0..n);
+        let j = rng.gen_range(i..=n.min(i + 8));
+        xs[i..j].to_vec()
+      } else {
+        (0..rng.gen_range(0..6)).map(|_| rng.gen_range(0..sigma + 1)).collect()
+      };
+      let expected = naive_locate(&xs, &pattern);
+      let mut found = sarmq.locate(&xs, &pattern);
+      found.sort_unstable();
+      assert_eq!(found, expected, "{xs:?} {pattern:?}");
+      assert_eq!(sarmq.count(&xs, &pattern), expected.len());
+      assert_eq!(narrow.count(&xs, &pattern), expected.len());
+    }
+  }
+}
+
+#[test]
+pub fn test_count_locate_str() {
+  let text = "abracadabra";
+  let sarmq = create_sarmq_from_bytes(text.as_bytes());
+  let count = |p: &str| sarmq.count(text.as_bytes(), p.as_bytes());
+  assert_eq!(count("abra"), 2);
+  assert_eq!(count("a"), 5);
+  assert_eq!(count("cad"), 1);
+  assert_eq!(count("abracadabrax"), 0);
+  assert_eq!(count("z"), 0);
+  assert_eq!(count(""), text.len());
+  let mut found = sarmq.locate(text.as_bytes(), b"bra");
+  found.sort_unstable();
+  assert_eq!(found, vec![1, 8]);
+  // occurrences are adjacent in the suffix array
+  let range = sarmq.sa_range(text.as_bytes(), b"a");
+  assert!(sarmq.sa()[range].iter().all(|&i| text.as_bytes()[i] == b'a'));
+}
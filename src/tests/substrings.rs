@@ -0,0 +1,29 @@
+use std::collections::HashSet;
+
+use crate::{create_sarmq, create_sarmq_from_str, try_create_sarmq_with_index};
+use rand::Rng;
+
+#[test]
+pub fn test_distinct_substrings_random() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..100 {
+    let n = rng.gen_range(0..60);
+    let sigma = rng.gen_range(1..=3.min(n.max(1)));
+    let xs: Vec<usize> = (0..n).map(|_| rng.gen_range(0..sigma)).collect();
+    let sarmq = create_sarmq(&xs);
+    let profile: Vec<usize> = (0..=n)
+      .map(|k| if k == 0 { 0 } else { xs.windows(k).collect::<HashSet<_>>().len() })
+      .collect();
+    assert_eq!(sarmq.distinct_kmers_per_length(), profile, "{xs:?}");
+    assert_eq!(sarmq.distinct_substrings(), profile.iter().sum::<usize>());
+    assert_eq!(try_create_sarmq_with_index::<u32>(&xs).unwrap().distinct_substrings(), profile.iter().sum::<usize>());
+  }
+}
+
+#[test]
+pub fn test_distinct_substrings_example() {
+  // a, b, n, an, ba, na, ana, ban, nan, anan, bana, nana, anana, banan, banana
+  let sarmq = create_sarmq_from_str("banana");
+  assert_eq!(sarmq.distinct_substrings(), 15);
+  assert_eq!(sarmq.distinct_kmers_per_length(), vec![0, 3, 3, 3, 3, 2, 1]);
+}
@@ -0,0 +1,96 @@
+use crate::bits::RankBits;
+use crate::rmq::{SuccinctRMQ, RMQ};
+use rand::Rng;
+
+fn leftmost_min(xs: &[usize], left: usize, right: usize) -> usize {
+  (left..=right).min_by_key(|&i| xs[i]).unwrap()
+}
+
+#[test]
+pub fn test_succinct_rmq_random() {
+  let mut rng = rand::thread_rng();
+  for _ in 0..50 {
+    // long enough for ranges over many blocks, few values for many ties
+    let n = rng.gen_range(1..3000);
+    let range = rng.gen_range(1..20);
+    let xs: Vec<usize> = (0..n).map(|_| rng.gen_range(0..range)).collect();
+    let rmq = SuccinctRMQ::create_succinct_rmq(&xs);
+    for _ in 0..200 {
+      let left = rng.gen_range(0..n);
+      let right = rng.gen_range(left..n);
+      assert_eq!(rmq.query(left, right), leftmost_min(&xs, left, right), "{left}..={right}");
+    }
+  }
+}
+
+#[test]
+pub fn test_succinct_rmq_monotone() {
+  let increasing: Vec<usize> = (0..2000).collect();
+  let decreasing: Vec<usize> = (0..2000).rev().collect();
+  let equal = vec![7; 2000];
+  let (a, b, c) = (
+    SuccinctRMQ::create_succinct_rmq(&increasing),
+    SuccinctRMQ::create_succinct_rmq(&decreasing),
+    SuccinctRMQ::create_succinct_rmq(&equal),
+  );
+  for (left, right) in [(0, 1999), (3, 1500), (700, 701), (1999, 1999)] {
+    assert_eq!(a.query(left, right), left);
+    assert_eq!(b.query(left, right), right);
+    assert_eq!(c.query(left, right), left);
+  }
+}
+
+#[test]
+pub fn test_succinct_rmq_same_as_rmq() {
+  let mut rng = rand::thread_rng();
+  for n in [1, 2, 10, 1000, 20000] {
+    // a walk of -1/0/+1 steps, which RMQ takes as well
+    let mut x = n;
+    let xs: Vec<usize> = (0..n).map(|_| { x = x + 1 - rng.gen_range(0..3); x }).collect();
+    let rmq = RMQ::<usize>::create_rmq(xs.iter().copied());
+    let succinct = SuccinctRMQ::create_succinct_rmq(xs.iter().copied());
+    assert_eq!(succinct.len(), n);
+    for _ in 0..1000 {
+      let left = rng.gen_range(0..n);
+      let right = rng.gen_range(left..n);
+      assert_eq!(xs[succinct.query(left, right)], xs[rmq.query(left, right)]);
+      assert_eq!(succinct.query(left, right), leftmost_min(&xs, left, right));
+    }
+  }
+}
+
+#[test]
+pub fn test_succinct_rmq_many_superblocks() {
+  let mut rng = rand::thread_rng();
+  // long runs up and down, for minima far apart and blocks with deep excess
+  let n = 300000;
+  let mut x: usize = n;
+  let xs: Vec<usize> = (0..n).map(|i| { x = if (i / 5000) % 2 == 0 { x + rng.gen_range(0..3) } else { x - rng.gen_range(0..2) }; x }).collect();
+  let rmq = SuccinctRMQ::create_succinct_rmq(&xs);
+  for _ in 0..500 {
+    let left = rng.gen_range(0..n);
+    let right = rng.gen_range(left..n);
+    assert_eq!(rmq.query(left, right), leftmost_min(&xs, left, right), "{left}..={right}");
+  }
+}
+
+#[test]
+pub fn test_rank_select_bits() {
+  let mut rng = rand::thread_rng();
+  // dense random bits, then ones far enough apart for their positions to be listed
+  let len = 1 << 25;
+  let bits: Vec<bool> = (0..len).map(|i| if i < 1 << 20 { rng.gen_bool(0.5) } else { i % 5000 == 0 }).collect();
+  let rank = RankBits::from_bits(bits.iter().copied());
+  let ones: Vec<usize> = (0..len).filter(|&i| bits[i]).collect();
+  for (k, &p) in ones.iter().enumerate() {
+    assert_eq!(rank.select1(k), p);
+    assert_eq!(rank.rank1(p), k);
+  }
+  for _ in 0..10000 {
+    let i = rng.gen_range(0..=len);
+    assert_eq!(rank.rank1(i), ones.partition_point(|&p| p < i));
+  }
+  assert_eq!(rank.rank1(len), ones.len());
+  let none = RankBits::from_bits((0..1000).map(|_| false));
+  assert_eq!(none.rank1(1000), 0);
+}